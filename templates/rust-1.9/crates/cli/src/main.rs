@@ -1,6 +1,6 @@
 //! CLI entry point.
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 
 /// {{project-name}} CLI
 #[derive(Debug, Parser)]
@@ -19,9 +19,17 @@ fn main() -> anyhow::Result<()> {
         )
         .init();
 
-    let cli = Cli::parse();
+    let build = project_core::build_info();
+    // `-V` prints the version; `--version` adds the commit and build time.
+    let matches = Cli::command().long_version(build.to_string()).get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
 
-    tracing::info!(version = project_core::version(), "starting");
+    tracing::info!(
+        version = build.version,
+        commit = build.commit,
+        built = build.timestamp,
+        "starting"
+    );
     println!("Hello, {}!", cli.name);
 
     Ok(())
@@ -2,9 +2,43 @@
 
 mod error;
 
+use std::fmt;
+
 pub use error::AppError;
 
 /// Returns the crate version.
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
+
+/// Version metadata captured when the crate was compiled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// Semantic version of the crate.
+    pub version: &'static str,
+    /// Abbreviated git commit hash, or `"unknown"` outside a git checkout.
+    pub commit: &'static str,
+    /// Build time as an RFC 3339 UTC timestamp (from `SOURCE_DATE_EPOCH` when
+    /// set), or `"unknown"`.
+    pub timestamp: &'static str,
+}
+
+impl fmt::Display for BuildInfo {
+    /// Formats as `0.1.0 (commit 1a2b3c4d5e6f, built 2025-01-31T12:00:00Z)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (commit {}, built {})",
+            self.version, self.commit, self.timestamp
+        )
+    }
+}
+
+/// Returns the version, git commit and build time of this build.
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: version(),
+        commit: env!("BUILD_GIT_COMMIT"),
+        timestamp: env!("BUILD_TIMESTAMP"),
+    }
+}
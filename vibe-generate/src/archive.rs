@@ -0,0 +1,81 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+
+/// Write `project_dir` as an uncompressed tar stream to `writer`, with every
+/// entry placed under a top-level `project_name/` directory.
+///
+/// Symlinks are archived as links rather than followed.
+pub fn write_tar<W: Write>(project_dir: &Path, project_name: &str, writer: W) -> io::Result<()> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    builder.append_dir_all(project_name, project_dir)?;
+    builder.into_inner()?.flush()
+}
+
+/// Archive formats [`extract`] understands, told apart by file extension.
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Zip,
+    TarGz,
+}
+
+/// Split `archive`'s file name into its stem and format, e.g.
+/// `web-app.tar.gz` -> (`web-app`, [`Format::TarGz`]).
+fn sniff(archive: &Path) -> Option<(&str, Format)> {
+    let name = archive.file_name()?.to_str()?;
+    [
+        (".zip", Format::Zip),
+        (".tar.gz", Format::TarGz),
+        (".tgz", Format::TarGz),
+    ]
+    .into_iter()
+    .find_map(|(extension, format)| {
+        let stem = name.get(..name.len().checked_sub(extension.len())?)?;
+        let tail = &name[stem.len()..];
+        (!stem.is_empty() && tail.eq_ignore_ascii_case(extension)).then_some((stem, format))
+    })
+}
+
+/// The file name of `archive` without its archive extension, if it has one
+/// [`extract`] supports.
+pub fn stem(archive: &Path) -> Option<&str> {
+    sniff(archive).map(|(stem, _)| stem)
+}
+
+/// Extract `archive`, a `.zip`, `.tar.gz` or `.tgz` file, into the existing
+/// directory `dest`.
+///
+/// Entries that would land outside `dest` (absolute paths or `..`) are not
+/// extracted.
+pub fn extract(archive: &Path, dest: &Path) -> io::Result<()> {
+    let (_, format) = sniff(archive).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} is not a .zip, .tar.gz or .tgz archive",
+                archive.display()
+            ),
+        )
+    })?;
+
+    let file = BufReader::new(File::open(archive)?);
+    match format {
+        Format::Zip => zip::ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(dest))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Format::TarGz => tar::Archive::new(GzDecoder::new(file)).unpack(dest),
+    }
+}
+
+/// The only entry of `dir` if it is a directory, as in archives made with
+/// `tar czf web-app.tar.gz web-app/`, which is then the archive's real root.
+pub fn single_root(dir: &Path) -> io::Result<Option<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?;
+    let (Some(first), None) = (entries.next().transpose()?, entries.next()) else {
+        return Ok(None);
+    };
+    Ok(first.file_type()?.is_dir().then(|| first.path()))
+}
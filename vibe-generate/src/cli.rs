@@ -1,10 +1,25 @@
-use clap::Parser;
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use std::path::PathBuf;
 
+use vibe_generate::{DEFAULT_MAX_SUBSTITUTIONS, DEFAULT_VERSION};
+
 /// Scaffold a new project from a boilerplate template.
+///
+/// Without a subcommand, a new project is generated.
 #[derive(Parser, Debug)]
-#[command(name = "vibe-generate", version, about)]
+#[command(
+    name = "vibe-generate",
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    group = ArgGroup::new("source").args(["template", "from_git", "from_archive"]).multiple(true)
+)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Template to use (e.g. "nextjs", "rust-cli"). If omitted, an interactive
     /// selection menu is shown.
     #[arg(short, long)]
@@ -12,11 +27,298 @@ pub struct Cli {
 
     /// Name of the new project (used as the output directory name and for
     /// placeholder replacement).
+    #[arg(short, long, required_unless_present_any = ["explain_source", "print_config", "spec", "list", "completions", "checksum"])]
+    pub name: Option<String>,
+
+    /// Directory where the project folder will be created. Defaults to the
+    /// user config's `output_dir`, else the current working directory.
+    #[arg(short, long)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Clone templates from a git repository (`URL`, or `URL#branch-or-tag`)
+    /// instead of using local ones. `--template` then names a sub-directory
+    /// of the repository; without it the repository root is the template.
+    #[arg(long, value_name = "URL")]
+    pub from_git: Option<String>,
+
+    /// Use the template in a local `.zip`, `.tar.gz` or `.tgz` archive instead
+    /// of local ones. `--template` then names a sub-directory of the archive;
+    /// without it the archive root is the template.
+    #[arg(long, value_name = "FILE", conflicts_with = "from_git")]
+    pub from_archive: Option<PathBuf>,
+
+    /// Read the template, name, output directory and extra placeholder values
+    /// from a TOML spec file. Flags given on the command line take precedence.
+    #[arg(long, value_name = "FILE")]
+    pub spec: Option<PathBuf>,
+
+    /// Initial version of the generated project, substituted for
+    /// `{{version}}`. Must be a valid semantic version.
+    #[arg(long, value_name = "VERSION", default_value = DEFAULT_VERSION, value_parser = parse_semver)]
+    pub project_version: String,
+
+    /// Give every generated file a fixed modification time so repeated runs
+    /// produce identical trees. Uses `SOURCE_DATE_EPOCH` when set (which also
+    /// enables this without the flag), otherwise the Unix epoch.
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// Fail instead of falling back to the templates compiled into the binary
+    /// when no `templates/` directory is found on the filesystem.
+    #[arg(long)]
+    pub no_embedded: bool,
+
+    /// Print how the `--template` value, or the `--from-git` or
+    /// `--from-archive` source, would be classified and resolved, then exit
+    /// without fetching or scaffolding anything.
+    #[arg(long, requires = "source")]
+    pub explain_source: bool,
+
+    /// Merge into an existing destination directory, overwriting files the
+    /// template also creates and leaving all others untouched.
+    #[arg(long, conflicts_with = "to_stdout")]
+    pub force: bool,
+
+    /// Initialize a git repository in the new project and commit the
+    /// generated files.
+    #[arg(long, conflicts_with = "to_stdout")]
+    pub git: bool,
+
+    /// Don't run the template's `post_setup` commands.
+    #[arg(long)]
+    pub no_hooks: bool,
+
+    /// Don't write `.vibe-manifest.toml`, the record of how the project was
+    /// generated. `update` then can't tell which files were changed.
+    #[arg(long)]
+    pub no_manifest: bool,
+
+    /// Output format: decorated text for people, or a single JSON document on
+    /// stdout (the result, or an error object on failure) for tools.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+
+    /// Print the SHA-256 checksum of the named embedded template and exit, to
+    /// confirm which version of it this binary carries.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub checksum: Option<String>,
+
+    /// Print the available templates, with their descriptions, and exit.
+    #[arg(long)]
+    pub list: bool,
+
+    /// Only offer templates tagged TAG (e.g. "rust", "web") in `--list` and
+    /// the interactive menu.
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    /// Print every file that would be created and the placeholder
+    /// substitutions in each, without writing anything.
+    #[arg(long, conflicts_with_all = ["to_stdout", "open", "review"])]
+    pub dry_run: bool,
+
+    /// Write the generated project to stdout as a tar stream instead of
+    /// creating it on disk. Status messages go to stderr.
+    #[arg(long, conflicts_with_all = ["output_dir", "open", "show_tree"])]
+    pub to_stdout: bool,
+
+    /// Log every directory, file and link as it is written to stderr, with
+    /// the template path of renamed ones and the placeholder substitutions
+    /// in each file, then a summary.
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Print a tree view of the generated project after it is created.
+    #[arg(long)]
+    pub show_tree: bool,
+
+    /// Open the new project in an editor after it is created. GUI editors are
+    /// left running; terminal editors take over until they exit. Skipped in
+    /// non-interactive and CI environments.
+    #[arg(long)]
+    pub open: bool,
+
+    /// Leave `.gitkeep` and `.keep` files out of the new project. The
+    /// directories they keep are still created.
+    #[arg(long)]
+    pub strip_keep_files: bool,
+
+    /// Enable the template feature NAME, generating its conditional files.
+    /// Repeatable. Interactive runs ask about the features not given.
+    #[arg(long = "feature", value_name = "NAME")]
+    pub features: Vec<String>,
+
+    /// Interactively edit generated files matching this glob (relative to the
+    /// project root) in an editor before finishing. Repeatable. Skipped in
+    /// non-interactive and CI environments.
+    #[arg(long, value_name = "GLOB")]
+    pub review: Vec<String>,
+
+    /// Editor command used by `--open` and `--review`. Defaults to `$VISUAL`,
+    /// then `$EDITOR`, then `code`.
+    #[arg(long, value_name = "COMMAND")]
+    pub editor: Option<String>,
+
+    /// Print the effective configuration, noting where each value came from,
+    /// then exit without scaffolding anything.
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Fail, before anything is written, if the template uses a placeholder
+    /// nothing gives a value for, instead of warning and leaving it as is.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Abort if more than this many placeholder substitutions would be made
+    /// in total, guarding against pathological templates.
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_SUBSTITUTIONS)]
+    pub max_substitutions: usize,
+
+    /// Language for prompts and messages (e.g. "en", "es"). Defaults to the
+    /// language of `$LANG`, falling back to English.
+    #[arg(long, value_name = "LANG")]
+    pub lang: Option<String>,
+
+    /// Seed the generator for `{{secret:N}}` and `{{password:N}}` so they are
+    /// reproducible. INSECURE: only for tests and deterministic fixtures.
+    #[arg(long, value_name = "SEED")]
+    pub secret_seed: Option<u64>,
+
+    /// Set a placeholder value, substituted for `{{KEY}}` in the template.
+    /// Repeatable; overrides spec-file vars and built-in placeholders.
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_assignment)]
+    pub set: Vec<(String, String)>,
+
+    /// Print a completion script for SHELL (bash, zsh, fish, powershell) to
+    /// stdout and exit. The names of the templates found now are offered for
+    /// `--template`.
+    #[arg(long, value_name = "SHELL", hide = true)]
+    pub completions: Option<Shell>,
+
+    /// Add a language-specific `.gitignore` to the new project (one of "go",
+    /// "node", "python", "rust"), merged with any the template ships.
+    #[arg(long, value_name = "LANGUAGE")]
+    pub gitignore: Option<String>,
+}
+
+/// Subcommands besides the default of generating a new project.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Re-apply a template over a project generated earlier. New files are
+    /// added and files you haven't changed are updated; files you changed are
+    /// reported as conflicts and left alone.
+    Update(UpdateArgs),
+}
+
+/// Arguments of `vibe-generate update`.
+#[derive(Args, Debug)]
+pub struct UpdateArgs {
+    /// Template to re-apply. Defaults to the one recorded in the project's
+    /// `.vibe-manifest.toml`.
+    #[arg(short, long)]
+    pub template: Option<String>,
+
+    /// Name of the project, as given when it was generated. It is also the
+    /// project's directory name.
     #[arg(short, long)]
     pub name: String,
 
-    /// Directory where the project folder will be created. Defaults to the
-    /// current working directory.
+    /// Directory containing the project folder. Defaults to the user
+    /// config's `output_dir`, else the current working directory.
     #[arg(short, long)]
     pub output_dir: Option<PathBuf>,
+
+    /// Version substituted for `{{version}}`. Defaults to the recorded one,
+    /// else 0.1.0.
+    #[arg(long, value_name = "VERSION", value_parser = parse_semver)]
+    pub project_version: Option<String>,
+
+    /// Set a placeholder value, overriding the recorded one. Repeatable.
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_assignment)]
+    pub set: Vec<(String, String)>,
+
+    /// Enable a template feature besides the recorded ones. Repeatable.
+    #[arg(long = "feature", value_name = "NAME")]
+    pub features: Vec<String>,
+
+    /// Report what would be added, updated and skipped without writing
+    /// anything.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Language for messages, as for generating.
+    #[arg(long, value_name = "LANG")]
+    pub lang: Option<String>,
+}
+
+/// How results are written to stdout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+/// Accept `value` only if it is a semantic version (`MAJOR.MINOR.PATCH` with
+/// optional `-pre.release` and `+build` suffixes).
+pub fn parse_semver(value: &str) -> Result<String, String> {
+    let invalid = |why: &str| format!("\"{value}\" is not a valid semantic version: {why}");
+
+    let (rest, build) = match value.split_once('+') {
+        Some((rest, build)) => (rest, Some(build)),
+        None => (value, None),
+    };
+    let (core, pre) = match rest.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (rest, None),
+    };
+
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.len() != 3 {
+        return Err(invalid("expected MAJOR.MINOR.PATCH"));
+    }
+    for part in parts {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid("version numbers must be non-negative integers"));
+        }
+        if part.len() > 1 && part.starts_with('0') {
+            return Err(invalid("version numbers must not have leading zeros"));
+        }
+    }
+
+    for (label, ids) in [("pre-release", pre), ("build metadata", build)] {
+        let Some(ids) = ids else { continue };
+        let valid = ids
+            .split('.')
+            .all(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'));
+        if !valid {
+            return Err(invalid(&format!(
+                "{label} must be dot-separated [0-9A-Za-z-] identifiers"
+            )));
+        }
+    }
+
+    Ok(value.to_string())
+}
+
+/// Split a `--set` value into its key and value at the first `=`. A
+/// `version` is checked like `--project-version`.
+fn parse_assignment(value: &str) -> Result<(String, String), String> {
+    let (key, val) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got \"{value}\" (no '=' found)"))?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(format!("expected KEY=VALUE, got \"{value}\" (empty key)"));
+    }
+    if key.contains("{{") || key.contains("}}") {
+        return Err(format!(
+            "\"{key}\" should be the bare key; use --set {}=... for {{{{{}}}}}",
+            key.trim_matches(['{', '}']),
+            key.trim_matches(['{', '}'])
+        ));
+    }
+    if key == "version" {
+        parse_semver(val)?;
+    }
+    Ok((key.to_string(), val.to_string()))
 }
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use console::Style;
+use serde::Deserialize;
+
+use crate::{cli, messages};
+
+/// Per-user defaults, read from `config.toml` in the user's config directory
+/// (see [`path`]). The spec file and command-line flags win over every value;
+/// `vars` in turn win over the built-in placeholders, but a template's own
+/// default for a placeholder it declares wins over them.
+///
+/// ```toml
+/// output_dir = "~/src"
+///
+/// [vars]
+/// license = "MIT"
+/// org = "acme"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Where projects are created when neither `--output-dir` nor the spec
+    /// file says. A leading `~` is the home directory.
+    pub output_dir: Option<PathBuf>,
+    /// Placeholder values, substituted for `{{key}}` unless a source that
+    /// wins over them gives one.
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+/// `$XDG_CONFIG_HOME/vibe-generate/config.toml`, with `XDG_CONFIG_HOME`
+/// defaulting to `~/.config`. `None` when no home directory is known.
+pub fn path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))?;
+    Some(config_home.join("vibe-generate").join("config.toml"))
+}
+
+/// Load the user config. A missing file is the same as an empty one; an
+/// unreadable or malformed file, including one with an invalid
+/// `vars.version`, is reported as a warning and ignored, so it never stops a
+/// run.
+pub fn load() -> Config {
+    let Some(path) = path() else {
+        return Config::default();
+    };
+    match read(&path) {
+        Ok(config) => config,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Config::default(),
+        Err(e) => {
+            eprintln!(
+                "{} Ignoring user config {}: {e}",
+                Style::new()
+                    .yellow()
+                    .bold()
+                    .apply_to(messages::get("label.warning")),
+                path.display()
+            );
+            Config::default()
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<Config> {
+    let contents = fs::read_to_string(path)?;
+    let mut config: Config = toml::from_str(&contents)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    // Checked like `--set version=...` and a spec file's version.
+    if let Some(version) = config.vars.get("version") {
+        cli::parse_semver(version).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("vars.version: {e}"))
+        })?;
+    }
+
+    if let Some(dir) = &config.output_dir {
+        if let (Ok(rest), Some(home)) = (dir.strip_prefix("~"), home_dir()) {
+            config.output_dir = Some(home.join(rest));
+        }
+    }
+    Ok(config)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
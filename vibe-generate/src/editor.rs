@@ -0,0 +1,158 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use globset::GlobSet;
+use walkdir::WalkDir;
+
+/// Work out which editor command to launch: an explicit override first, then
+/// `$VISUAL`, then `$EDITOR`, and finally `code` if it is on the `PATH`.
+pub fn resolve(override_cmd: Option<&str>) -> Option<String> {
+    if let Some(cmd) = override_cmd.filter(|c| !c.trim().is_empty()) {
+        return Some(cmd.to_string());
+    }
+
+    for var in ["VISUAL", "EDITOR"] {
+        if let Ok(cmd) = env::var(var) {
+            if !cmd.trim().is_empty() {
+                return Some(cmd);
+            }
+        }
+    }
+
+    on_path("code").then(|| "code".to_string())
+}
+
+/// Editors that open a window of their own, so they can be left running after
+/// we exit. Any other editor is assumed to need the terminal.
+const GUI_EDITORS: &[&str] = &[
+    "atom",
+    "code",
+    "code-insiders",
+    "codium",
+    "cursor",
+    "fleet",
+    "gedit",
+    "gvim",
+    "idea",
+    "kate",
+    "mate",
+    "mvim",
+    "subl",
+    "zed",
+];
+
+/// Open `path` in `editor`: a GUI editor is launched without waiting for it,
+/// while a terminal editor such as vim or nano runs in the foreground until
+/// it exits.
+///
+/// The command may carry its own arguments (e.g. `"code -n"`); they are split
+/// on whitespace and `path` is appended last.
+pub fn open(editor: &str, path: &Path) -> io::Result<()> {
+    let program = editor.split_whitespace().next().map(Path::new);
+    let name = program.and_then(Path::file_stem).and_then(|n| n.to_str());
+    if name.is_some_and(|name| GUI_EDITORS.contains(&name)) {
+        spawn_detached(editor, path)
+    } else {
+        edit_blocking(editor, path)
+    }
+}
+
+/// Launch `editor` on `path` without waiting for it to exit.
+///
+/// The command may carry its own arguments (e.g. `"code -n"`); they are split
+/// on whitespace and `path` is appended last.
+fn spawn_detached(editor: &str, path: &Path) -> io::Result<()> {
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty editor command"))?;
+
+    Command::new(program)
+        .args(parts)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    Ok(())
+}
+
+/// Run `editor` on `path` and wait for it to exit, failing if it exits
+/// unsuccessfully.
+pub fn edit_blocking(editor: &str, path: &Path) -> io::Result<()> {
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty editor command"))?;
+
+    // Our stdout may be a pipe; stderr is the terminal the editor is drawn on.
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .stdout(io::stderr())
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "Editor \"{editor}\" exited with {status}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Open every file under `project_dir` whose relative path matches `patterns`
+/// in `editor`, one at a time, and write the edited content back.
+///
+/// Each file is edited through a temporary copy (keeping its extension for
+/// syntax highlighting), so an editor that fails leaves the generated file
+/// untouched. Returns the reviewed paths, relative to `project_dir`.
+pub fn review(project_dir: &Path, patterns: &GlobSet, editor: &str) -> io::Result<Vec<PathBuf>> {
+    let mut reviewed = Vec::new();
+
+    for entry in WalkDir::new(project_dir).sort_by_file_name() {
+        let entry = entry.map_err(io::Error::other)?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(rel) = entry.path().strip_prefix(project_dir) else {
+            continue;
+        };
+        if !patterns.is_match(rel) {
+            continue;
+        }
+
+        let suffix = entry
+            .path()
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let temp = tempfile::Builder::new()
+            .prefix("vibe-review-")
+            .suffix(&suffix)
+            .tempfile()?;
+        fs::copy(entry.path(), temp.path())?;
+
+        edit_blocking(editor, temp.path())?;
+        fs::copy(temp.path(), entry.path())?;
+
+        reviewed.push(rel.to_path_buf());
+    }
+
+    Ok(reviewed)
+}
+
+/// Whether an executable named `program` exists in one of the `PATH` entries.
+fn on_path(program: &str) -> bool {
+    let Some(paths) = env::var_os("PATH") else {
+        return false;
+    };
+
+    env::split_paths(&paths).any(|dir| {
+        let candidate = dir.join(program);
+        candidate.is_file() || (cfg!(windows) && candidate.with_extension("cmd").is_file())
+    })
+}
@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use vibe_generate::ScaffoldError;
+
+use crate::messages;
+
+/// A failure of the command-line tool itself, rather than of a scaffold (see
+/// [`vibe_generate::ScaffoldError`]). `kind` is a stable id for `--format
+/// json`, and the message is kept in English for machine output as well as
+/// in the selected language for people. It can wrap the [`ScaffoldError`] it
+/// explains, so a friendlier message doesn't lose the structured error.
+#[derive(Debug)]
+pub struct CliError {
+    pub kind: &'static str,
+    /// The message in English.
+    pub message: String,
+    localized: String,
+    source: Option<Box<ScaffoldError>>,
+}
+
+impl CliError {
+    /// An error whose message has no translation.
+    pub fn new(kind: &'static str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        CliError {
+            kind,
+            localized: message.clone(),
+            message,
+            source: None,
+        }
+    }
+
+    /// An error with the catalog message `id`, filled in from `args`.
+    pub fn localized(kind: &'static str, id: &str, args: &[(&str, &dyn Display)]) -> Self {
+        CliError {
+            kind,
+            message: messages::fill_english(id, args),
+            localized: messages::fill(id, args),
+            source: None,
+        }
+    }
+
+    /// This error, as the explanation of `source`.
+    pub fn caused_by(self, source: ScaffoldError) -> Self {
+        CliError {
+            source: Some(Box::new(source)),
+            ..self
+        }
+    }
+}
+
+/// The [`ScaffoldError`] behind `error`, whether it is one or a [`CliError`]
+/// wrapping one.
+pub fn scaffold_error<'a>(error: &'a (dyn Error + 'static)) -> Option<&'a ScaffoldError> {
+    error.downcast_ref::<ScaffoldError>().or_else(|| {
+        error
+            .downcast_ref::<CliError>()
+            .and_then(|e| e.source.as_deref())
+    })
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localized)
+    }
+}
+
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|e| e as _)
+    }
+}
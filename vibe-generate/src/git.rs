@@ -0,0 +1,60 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Run `git` with `args` in `dir`, failing with git's own error output if it
+/// exits unsuccessfully. A missing `git` binary surfaces as
+/// [`io::ErrorKind::NotFound`].
+pub fn run(dir: &Path, args: &[&str]) -> io::Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            stderr.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Whether `dir` is inside an existing git work tree.
+pub fn inside_work_tree(dir: &Path) -> io::Result<bool> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()?;
+    Ok(output.status.success() && output.stdout.starts_with(b"true"))
+}
+
+/// Initialize a repository in `dir` (unless `existing` says one already
+/// covers it), stage everything under `dir` and commit it with `message`.
+///
+/// In an existing repository only the paths under `dir` are committed, so
+/// changes the user had staged elsewhere are left alone.
+pub fn init_and_commit(dir: &Path, existing: bool, message: &str) -> io::Result<()> {
+    if !existing {
+        run(dir, &["init", "-q"])?;
+    }
+    run(dir, &["add", "-A", "--", "."])?;
+    run(dir, &["commit", "-q", "-m", message, "--", "."])
+}
+
+/// Shallow-clone `url` into `dest`, checking out `reference` (a branch or
+/// tag) instead of the default branch when given.
+pub fn shallow_clone(url: &str, reference: Option<&str>, dest: &Path) -> io::Result<()> {
+    let parent = dest.parent().unwrap_or(Path::new("."));
+    let dest = dest.to_string_lossy();
+    let mut args = vec!["clone", "-q", "--depth", "1"];
+    if let Some(reference) = reference {
+        args.extend(["--branch", reference]);
+    }
+    args.extend(["--", url, &dest]);
+    run(parent, &args)
+}
@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Languages with an embedded `.gitignore` snippet, in display order.
+pub const LANGUAGES: &[&str] = &["go", "node", "python", "rust"];
+
+const GO: &str = "\
+# Binaries
+*.exe
+*.exe~
+*.dll
+*.so
+*.dylib
+*.test
+*.out
+bin/
+
+# Dependencies
+vendor/
+
+# Environment
+.env
+";
+
+const NODE: &str = "\
+# Dependencies
+node_modules/
+
+# Build output
+dist/
+build/
+.next/
+out/
+coverage/
+
+# Logs
+npm-debug.log*
+yarn-debug.log*
+yarn-error.log*
+pnpm-debug.log*
+
+# Environment
+.env
+.env*.local
+";
+
+const PYTHON: &str = "\
+# Byte-compiled files
+__pycache__/
+*.py[cod]
+
+# Packaging
+build/
+dist/
+*.egg-info/
+
+# Virtual environments
+.venv/
+venv/
+
+# Tooling caches
+.pytest_cache/
+.mypy_cache/
+.ruff_cache/
+.coverage
+
+# Environment
+.env
+";
+
+const RUST: &str = "\
+# Build output
+/target
+target/
+
+# Environment
+.env
+";
+
+/// Return the embedded `.gitignore` snippet for `language`, if supported.
+pub fn snippet(language: &str) -> Option<&'static str> {
+    match language.to_ascii_lowercase().as_str() {
+        "go" => Some(GO),
+        "node" => Some(NODE),
+        "python" => Some(PYTHON),
+        "rust" => Some(RUST),
+        _ => None,
+    }
+}
+
+/// Create or extend `project_dir/.gitignore` with the snippet for `language`.
+///
+/// Entries the file already contains are not repeated, and comment lines are
+/// only carried over when they introduce at least one new entry.
+pub fn apply(project_dir: &Path, language: &str) -> io::Result<()> {
+    let snippet = snippet(language).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Unsupported .gitignore language \"{language}\". Supported: {}",
+                LANGUAGES.join(", ")
+            ),
+        )
+    })?;
+
+    let path = project_dir.join(".gitignore");
+    let existing = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+
+    let mut seen: HashSet<&str> = existing
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect();
+
+    // Group the snippet into comment-headed sections so a header is dropped
+    // along with its entries when all of them are already present.
+    let mut additions = String::new();
+    let mut header: Vec<&str> = Vec::new();
+    for line in snippet.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            header.clear();
+        } else if trimmed.starts_with('#') {
+            header.push(line);
+        } else if seen.insert(trimmed) {
+            if !header.is_empty() {
+                if !additions.is_empty() {
+                    additions.push('\n');
+                }
+                for h in header.drain(..) {
+                    additions.push_str(h);
+                    additions.push('\n');
+                }
+            }
+            additions.push_str(line);
+            additions.push('\n');
+        }
+    }
+
+    if additions.is_empty() {
+        return Ok(());
+    }
+
+    let mut contents = existing;
+    if !contents.is_empty() {
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push('\n');
+    }
+    contents.push_str(&additions);
+
+    fs::write(&path, contents)
+}
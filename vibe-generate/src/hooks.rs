@@ -0,0 +1,26 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+
+/// Run `command` through the platform shell (`sh -c`, or `cmd /C` on
+/// Windows) in `dir`, streaming its output, and wait for it to exit.
+///
+/// With `stdout_to_stderr` the command's standard output goes to our
+/// standard error, keeping stdout free for a tar stream.
+pub fn run(command: &str, dir: &Path, stdout_to_stderr: bool) -> io::Result<ExitStatus> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command).current_dir(dir).stdin(Stdio::null());
+    if stdout_to_stderr {
+        cmd.stdout(io::stderr());
+    }
+
+    cmd.status()
+}
@@ -0,0 +1,49 @@
+//! Scaffold new projects from boilerplate templates.
+//!
+//! This is the library behind the `vibe-generate` CLI. A template is a
+//! directory whose files are copied into a new project folder, with `{{key}}`
+//! placeholders in file contents and names replaced along the way.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use vibe_generate::{list_templates, scaffold, ScaffoldError, ScaffoldOptions};
+//!
+//! fn main() -> Result<(), ScaffoldError> {
+//!     let templates_root = Path::new("templates");
+//!     for template in list_templates(templates_root)? {
+//!         println!("{}: {}", template.id, template.label());
+//!     }
+//!
+//!     let mut options = ScaffoldOptions::new("my-app", "projects");
+//!     options
+//!         .placeholders
+//!         .insert("version".to_string(), "1.0.0".to_string());
+//!
+//!     let template_dir = templates_root.join("rust-1.9");
+//!     match scaffold(&template_dir, &options, &mut |_done, _total, _path| {}) {
+//!         Ok(report) => println!("{} placeholders replaced", report.substitutions),
+//!         Err(ScaffoldError::DestinationExists(dest)) => {
+//!             eprintln!("{} already exists; set `force` to merge", dest.display())
+//!         }
+//!         Err(e) => return Err(e),
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+pub mod manifest;
+pub mod project_manifest;
+mod project_name;
+pub mod scaffold;
+mod secrets;
+mod vibeignore;
+
+pub use manifest::{Manifest, TemplateInfo};
+pub use project_manifest::{ProjectManifest, PROJECT_MANIFEST_FILE};
+pub use project_name::{crate_name_problem, validate_project_name};
+pub use scaffold::{
+    list_templates, list_templates_embedded, plan_update, scaffold, scaffold_embedded,
+    template_checksum, Plan, ScaffoldError, ScaffoldOptions, ScaffoldReport, UpdatePlan,
+    WrittenFile, DEFAULT_MAX_SUBSTITUTIONS, DEFAULT_VERSION, KEEP_FILES,
+};
@@ -1,46 +1,99 @@
+mod archive;
 mod cli;
-mod scaffold;
+mod config;
+mod editor;
+mod error;
+mod git;
+mod gitignore;
+mod hooks;
+mod messages;
+mod reporter;
+mod spec;
+mod suggest;
+mod update;
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::env;
-use std::path::PathBuf;
+use std::error::Error;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::SystemTime;
 
-use clap::Parser;
-use console::Style;
-use dialoguer::Select;
+use clap::builder::PossibleValuesParser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use console::{Style, Term};
+use dialoguer::{Confirm, Input, Select};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use include_dir::{include_dir, Dir};
+use indicatif::{ProgressBar, ProgressStyle};
 
-use cli::Cli;
-use scaffold::{
-    list_templates, list_templates_embedded, resolve_template_dir, scaffold, scaffold_embedded,
+use clap_complete::Shell;
+use cli::{Cli, Command, OutputFormat};
+use error::{scaffold_error, CliError};
+use vibe_generate::manifest::{DeclaredPlaceholder, Feature, TemplateInfo};
+use vibe_generate::scaffold::{
+    build_placeholders, list_templates, list_templates_embedded, plan, plan_embedded,
+    reproducible_mtime, resolve_template_dir, set_mtimes, template_checksum, ScaffoldOptions,
 };
+use vibe_generate::{crate_name_problem, validate_project_name, ProjectManifest, ScaffoldError};
 
 /// All templates are embedded at compile time so the binary is self-contained.
 static EMBEDDED_TEMPLATES: Dir = include_dir!("$CARGO_MANIFEST_DIR/../templates");
 
-/// Locate the `templates/` directory on the filesystem (for local development).
+/// Maximum number of files listed by `--show-tree` before summarizing.
+const TREE_FILE_LIMIT: usize = 100;
+
+/// With more templates than this on offer, the interactive menu first asks
+/// for a tag and then lists only the templates carrying it.
+const GROUPED_MENU_THRESHOLD: usize = 8;
+
+/// Locate the `templates/` directory on the filesystem.
+///
+/// Candidates are tried in order:
+/// 1. `$CARGO_MANIFEST_DIR/../templates` (during `cargo run`),
+/// 2. `templates/` in the current directory or any of its ancestors,
+/// 3. `<exe dir>/templates`, then `<exe dir>/../share/vibe-generate/templates`
+///    for packaged installs that ship templates next to the binary.
 fn find_templates_root() -> Option<PathBuf> {
     // During `cargo run` the manifest dir is set.
     if let Ok(manifest) = env::var("CARGO_MANIFEST_DIR") {
         let candidate = PathBuf::from(manifest).join("../templates");
         if candidate.is_dir() {
-            return Some(candidate.canonicalize().ok()?);
+            return candidate.canonicalize().ok();
         }
     }
 
-    // Fallback: walk up from the current working directory.
-    let mut dir = env::current_dir().ok()?;
-    loop {
-        let candidate = dir.join("templates");
-        if candidate.is_dir() {
-            return Some(candidate);
-        }
-        if !dir.pop() {
-            break;
+    // Fallback: walk up from the current working directory. The candidate is
+    // canonicalized as well so a symlinked `templates/` behaves exactly like
+    // the manifest-relative one.
+    if let Ok(mut dir) = env::current_dir() {
+        loop {
+            let candidate = dir.join("templates");
+            if candidate.is_dir() {
+                return candidate.canonicalize().ok();
+            }
+            if !dir.pop() {
+                break;
+            }
         }
     }
 
-    None
+    // Finally, look next to the executable (skipped if it can't be located).
+    let exe_dir = env::current_exe()
+        .and_then(|exe| exe.canonicalize())
+        .ok()?
+        .parent()?
+        .to_path_buf();
+    [
+        exe_dir.join("templates"),
+        exe_dir.join("../share/vibe-generate/templates"),
+    ]
+    .into_iter()
+    .find(|candidate| candidate.is_dir())
+    .and_then(|candidate| candidate.canonicalize().ok())
 }
 
 /// Represents where templates come from.
@@ -49,95 +102,1594 @@ enum TemplateSource {
     Embedded,
 }
 
-fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Cli::parse();
+impl TemplateSource {
+    /// Where `template` comes from, for `{{template-source}}`. Embedded
+    /// templates are versioned by the binary that carries them.
+    fn provenance(&self, template: &str) -> String {
+        match self {
+            TemplateSource::Filesystem(root) => {
+                format!("file:{}", resolve_template_dir(root, template).display())
+            }
+            TemplateSource::Embedded => format!(
+                "embedded:{template} (vibe-generate {})",
+                env!("CARGO_PKG_VERSION")
+            ),
+        }
+    }
+}
+
+/// How `--explain-source` classified `--template`, and what it would resolve
+/// to.
+struct Explanation {
+    /// The value explained: `--template`, else the `--from-git` or
+    /// `--from-archive` source.
+    value: String,
+    /// `name`, `path`, `url`, `git` or `archive`.
+    kind: &'static str,
+    /// Where templates are looked up, or would be fetched from.
+    source: Option<String>,
+    resolves_to: Option<String>,
+    /// Why it does not resolve, if it doesn't.
+    unresolved: Option<String>,
+}
+
+impl Explanation {
+    fn new(value: &str, kind: &'static str) -> Self {
+        Explanation {
+            value: value.to_string(),
+            kind,
+            source: None,
+            resolves_to: None,
+            unresolved: None,
+        }
+    }
+}
+
+/// Explain how `value` would be classified and resolved against `source`
+/// without touching the filesystem beyond template discovery. A template id
+/// is a name even when it looks like a path, as nested ids (`rust/cli`) do.
+fn explain_source(value: &str, source: &TemplateSource, available: &[String]) -> Explanation {
+    let kind = if available.iter().any(|t| t == value) {
+        "name"
+    } else if value.contains("://") || value.starts_with("git+") {
+        "url"
+    } else if value.contains('/') || value.contains('\\') || value.starts_with('.') {
+        "path"
+    } else {
+        "name"
+    };
+    let mut explanation = Explanation::new(value, kind);
+
+    if kind != "name" {
+        explanation.unresolved = Some(
+            "only template names are supported; use --from-git or --from-archive \
+             for other sources"
+                .to_string(),
+        );
+        return explanation;
+    }
+
+    explanation.source = Some(match source {
+        TemplateSource::Filesystem(root) => format!("filesystem ({})", root.display()),
+        TemplateSource::Embedded => "embedded (compiled into the binary)".to_string(),
+    });
+
+    if !available.iter().any(|t| t == value) {
+        explanation.unresolved = Some(format!("not found; available: {}", available.join(", ")));
+        return explanation;
+    }
+
+    explanation.resolves_to = Some(match source {
+        TemplateSource::Filesystem(root) => resolve_template_dir(root, value).display().to_string(),
+        TemplateSource::Embedded => format!("embedded template \"{value}\""),
+    });
+    explanation
+}
+
+/// Explain the `--from-git` or `--from-archive` source in `args`, if there
+/// is one, without fetching it. What the clone or archive holds is only known
+/// once fetched, so this is the plan for fetching it.
+fn explain_fetch(args: &Cli) -> Option<Explanation> {
+    let subpath = args.template.as_deref();
+    let within = |root: &str| match subpath {
+        Some(subpath) => format!("{subpath} in {root}"),
+        None => root.to_string(),
+    };
+
+    if let Some(spec) = &args.from_git {
+        let (url, reference) = split_git_spec(spec);
+        let mut explanation = Explanation::new(subpath.unwrap_or(spec), "git");
+        explanation.source = Some(format!("git repository {url}"));
+        explanation.resolves_to = Some(within(&format!(
+            "a shallow clone of {}",
+            reference.unwrap_or("the default branch")
+        )));
+        return Some(explanation);
+    }
+
+    let path = args.from_archive.as_ref()?;
+    let mut explanation = Explanation::new(
+        &subpath.map_or_else(|| path.display().to_string(), str::to_string),
+        "archive",
+    );
+    explanation.source = Some(format!("archive {}", path.display()));
+    if archive::stem(path).is_some() {
+        explanation.resolves_to = Some(within(
+            "the extracted archive, or the single directory it holds",
+        ));
+    } else {
+        explanation.unresolved = Some("not a .zip, .tar.gz or .tgz file".to_string());
+    }
+    Some(explanation)
+}
+
+/// Print `explanation`, as JSON or for a person, and exit with status 1 if it
+/// does not resolve.
+fn print_explanation(explanation: &Explanation, json: bool) {
+    if json {
+        println!("{:#}", reporter::explanation_json(explanation));
+    } else {
+        let bold = Style::new().bold();
+        let green = Style::new().green().bold();
+        let red = Style::new().red().bold();
+
+        println!(
+            "{} Template {}",
+            bold.apply_to("=>"),
+            green.apply_to(&explanation.value)
+        );
+        let kind = match explanation.kind {
+            "name" => "template name",
+            "url" => "URL",
+            "git" => "git repository (not fetched)",
+            "archive" => "archive (not extracted)",
+            kind => kind,
+        };
+        println!("  classified as: {kind}");
+        if let Some(source) = &explanation.source {
+            println!("  source:        {source}");
+        }
+        if let Some(resolves_to) = &explanation.resolves_to {
+            println!("  resolves to:   {resolves_to}");
+        }
+        if let Some(reason) = &explanation.unresolved {
+            println!(
+                "  {} {reason}",
+                red.apply_to(messages::get("label.unresolved"))
+            );
+        }
+    }
+
+    if explanation.unresolved.is_some() {
+        process::exit(1);
+    }
+}
+
+/// Launch the user's editor on `project_dir`. Failures only warn: the project
+/// has already been created successfully at this point.
+fn open_in_editor(project_dir: &Path, editor_override: Option<&str>) {
+    let yellow = Style::new().yellow().bold();
+
+    if !is_interactive() {
+        eprintln!(
+            "{} Skipping --open in a non-interactive environment",
+            yellow.apply_to(messages::get("label.warning"))
+        );
+        return;
+    }
+
+    let Some(editor) = editor::resolve(editor_override) else {
+        eprintln!(
+            "{} No editor configured; set $VISUAL or $EDITOR, or pass --editor",
+            yellow.apply_to(messages::get("label.warning"))
+        );
+        return;
+    };
+
+    if let Err(e) = editor::open(&editor, project_dir) {
+        eprintln!(
+            "{} Failed to launch editor \"{editor}\": {e}",
+            yellow.apply_to(messages::get("label.warning"))
+        );
+    }
+}
+
+/// The templates in `templates` tagged `tag` (ignoring case), or all of them
+/// without a tag. Errors, listing the tags in use, when none match.
+fn filter_by_tag<'a>(
+    templates: &'a [TemplateInfo],
+    tag: Option<&str>,
+) -> Result<Vec<&'a TemplateInfo>, CliError> {
+    let Some(tag) = tag else {
+        return Ok(templates.iter().collect());
+    };
+    let matching: Vec<&TemplateInfo> = templates
+        .iter()
+        .filter(|t| {
+            t.manifest
+                .tags
+                .iter()
+                .any(|own| own.eq_ignore_ascii_case(tag))
+        })
+        .collect();
+    if matching.is_empty() {
+        let tags: BTreeSet<&str> = templates
+            .iter()
+            .flat_map(|t| t.manifest.tags.iter().map(String::as_str))
+            .collect();
+        let tags: Vec<&str> = tags.into_iter().collect();
+        return Err(CliError::localized(
+            "no_tagged_templates",
+            "error.no-tagged-templates",
+            &[("tag", &tag), ("tags", &tags.join(", "))],
+        ));
+    }
+    Ok(matching)
+}
+
+/// Group `templates` by tag for the two-step menu, in tag order. A template
+/// appears under each of its tags; untagged ones are grouped last under
+/// `None`.
+fn group_by_tag<'a>(
+    templates: &[&'a TemplateInfo],
+) -> Vec<(Option<String>, Vec<&'a TemplateInfo>)> {
+    let mut tagged: BTreeMap<&str, Vec<&TemplateInfo>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for &template in templates {
+        if template.manifest.tags.is_empty() {
+            untagged.push(template);
+        }
+        for tag in &template.manifest.tags {
+            tagged.entry(tag).or_default().push(template);
+        }
+    }
+
+    let mut groups: Vec<_> = tagged
+        .into_iter()
+        .map(|(tag, templates)| (Some(tag.to_string()), templates))
+        .collect();
+    if !untagged.is_empty() {
+        groups.push((None, untagged));
+    }
+    groups
+}
+
+/// Ask which of `templates` to use. With `grouped`, and many to choose from,
+/// a tag is asked for first. Returns the template id.
+fn select_template(templates: &[&TemplateInfo], grouped: bool) -> Result<String, Box<dyn Error>> {
+    let mut candidates = templates.to_vec();
+
+    let groups = group_by_tag(templates);
+    if grouped && templates.len() > GROUPED_MENU_THRESHOLD && groups.len() > 1 {
+        let labels: Vec<String> = groups
+            .iter()
+            .map(|(tag, members)| {
+                let tag = match tag {
+                    Some(tag) => tag.clone(),
+                    None => messages::get("label.uncategorized"),
+                };
+                format!("{tag} ({})", members.len())
+            })
+            .collect();
+        let selection = Select::new()
+            .with_prompt(messages::get("prompt.select-tag"))
+            .items(&labels)
+            .default(0)
+            .interact()?;
+        candidates = groups[selection].1.clone();
+    }
+
+    let labels: Vec<String> = candidates.iter().map(|t| t.label()).collect();
+    let selection = Select::new()
+        .with_prompt(messages::get("prompt.select-template"))
+        .items(&labels)
+        .default(0)
+        .interact()?;
+    Ok(candidates[selection].id.clone())
+}
+
+/// Whether we can ask the user questions: not in CI, and both stdin and the
+/// terminal prompts are drawn on (stderr) are terminals.
+fn is_interactive() -> bool {
+    env::var_os("CI").is_none() && io::stdin().is_terminal() && Term::stderr().is_term()
+}
+
+/// Run the template's `post_setup` commands in `project_dir`, asking first in
+/// an interactive terminal. Stops at the first failing command and reports
+/// it; the generated files are left in place either way. Returns whether
+/// every command that ran succeeded.
+fn run_hooks(commands: &[String], project_dir: &Path, to_stdout: bool) -> io::Result<bool> {
+    let bold = Style::new().bold();
+    let red = Style::new().red().bold();
+
+    if is_interactive() {
+        eprintln!();
+        for command in commands {
+            eprintln!("  {command}");
+        }
+        let proceed = Confirm::new()
+            .with_prompt(messages::fill(
+                "prompt.run-hooks",
+                &[("path", &project_dir.display())],
+            ))
+            .default(false)
+            .interact()
+            .map_err(io::Error::other)?;
+        if !proceed {
+            eprintln!("{}", messages::get("status.hooks-skipped"));
+            return Ok(true);
+        }
+    }
+
+    for command in commands {
+        eprintln!(
+            "{} {}",
+            bold.apply_to("=>"),
+            messages::fill("status.running-hook", &[("command", command)])
+        );
+        let status = match hooks::run(command, project_dir, to_stdout) {
+            Ok(status) if status.success() => continue,
+            Ok(status) => status.to_string(),
+            Err(e) => e.to_string(),
+        };
+        eprintln!(
+            "{} {}",
+            red.apply_to(messages::get("label.error")),
+            messages::fill(
+                "error.hook-failed",
+                &[("command", command), ("status", &status)]
+            )
+        );
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Shallow-clone the repository named by a `--from-git` value (`URL` or
+/// `URL#ref`) into `temp`, and return the template directory inside it:
+/// `subpath` if given, else the repository root.
+fn clone_template(
+    spec: &str,
+    subpath: Option<&str>,
+    temp: &Path,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let (url, reference) = split_git_spec(spec);
+
+    // Name the checkout after the repository so it reads well as a template
+    // name, e.g. `.../my-templates.git` -> `my-templates`.
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .map(|n| n.trim_end_matches(".git"))
+        .filter(|n| !n.is_empty())
+        .unwrap_or("template");
+    let repo = temp.join(name);
+
+    git::shallow_clone(url, reference, &repo).map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            CliError::localized("git_not_found", "error.git-not-found", &[])
+        } else {
+            CliError::localized(
+                "clone_failed",
+                "error.clone-failed",
+                &[("url", &url), ("error", &e)],
+            )
+        }
+    })?;
+    // The template is the checked-out tree, not its history.
+    fs::remove_dir_all(repo.join(".git"))?;
+
+    Ok(template_subdir(repo, subpath, url)?)
+}
+
+/// Split a `--from-git` value into the repository URL and the `#ref`, if any.
+fn split_git_spec(spec: &str) -> (&str, Option<&str>) {
+    match spec.rsplit_once('#') {
+        Some((url, reference)) if !reference.is_empty() => (url, Some(reference)),
+        _ => (spec.trim_end_matches('#'), None),
+    }
+}
+
+/// Extract the `--from-archive` file `path` into `temp`, and return the
+/// template directory inside it: `subpath` if given, else the archive root.
+/// An archive holding a single directory has that directory as its root.
+fn extract_template(
+    path: &Path,
+    subpath: Option<&str>,
+    temp: &Path,
+) -> Result<PathBuf, Box<dyn Error>> {
+    // Like a clone, name the root after the archive: `web-app.tar.gz` ->
+    // `web-app`.
+    let stem = archive::stem(path).ok_or_else(|| {
+        CliError::localized(
+            "unsupported_archive",
+            "error.unsupported-archive",
+            &[("path", &path.display())],
+        )
+    })?;
+    let dir = temp.join(stem);
+    fs::create_dir(&dir)?;
+    archive::extract(path, &dir).map_err(|e| {
+        CliError::localized(
+            "extract_failed",
+            "error.extract-failed",
+            &[("path", &path.display()), ("error", &e)],
+        )
+    })?;
+
+    let root = match archive::single_root(&dir)? {
+        Some(inner) => {
+            // Keep the archive's name for the template rather than whatever
+            // its top-level directory is called.
+            let unwrapped = temp.join(format!(".{stem}"));
+            fs::rename(&inner, &unwrapped)?;
+            fs::remove_dir(&dir)?;
+            fs::rename(&unwrapped, &dir)?;
+            dir
+        }
+        None => dir,
+    };
+    Ok(template_subdir(root, subpath, &path.display().to_string())?)
+}
+
+/// The `--template` `subpath` of the fetched `root` (a cloned repository or
+/// extracted archive, from `origin`), or `root` itself.
+fn template_subdir(
+    root: PathBuf,
+    subpath: Option<&str>,
+    origin: &str,
+) -> Result<PathBuf, CliError> {
+    let Some(subpath) = subpath else {
+        return Ok(root);
+    };
+    let relative = Path::new(subpath);
+    if !relative
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)))
+    {
+        return Err(CliError::localized(
+            "invalid_template_path",
+            "error.invalid-template-path",
+            &[("subpath", &subpath), ("origin", &origin)],
+        ));
+    }
+    let template_dir = root.join(relative);
+    if !template_dir.is_dir() {
+        return Err(CliError::localized(
+            "template_not_found",
+            "error.template-path-not-found",
+            &[("subpath", &subpath), ("origin", &origin)],
+        ));
+    }
+    Ok(template_dir)
+}
+
+/// Put the new project under version control for `--git`. Problems only
+/// warn: the project itself was created successfully.
+fn init_git(project_dir: &Path, template_name: &str) {
+    let yellow = Style::new().yellow().bold();
+    let warn = |message: String| {
+        eprintln!(
+            "{} {message}",
+            yellow.apply_to(messages::get("label.warning"))
+        );
+    };
+
+    let existing = match git::inside_work_tree(project_dir) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            warn(messages::get("warning.git-not-found"));
+            return;
+        }
+        Err(e) => {
+            warn(messages::fill("warning.git-failed", &[("error", &e)]));
+            return;
+        }
+    };
+
+    if existing {
+        let commit = is_interactive()
+            && Confirm::new()
+                .with_prompt(messages::get("prompt.git-commit-existing"))
+                .default(false)
+                .interact()
+                .unwrap_or(false);
+        if !commit {
+            warn(messages::get("warning.git-existing-skipped"));
+            return;
+        }
+    }
+
+    let message = format!("Initial commit from template {template_name}");
+    if let Err(e) = git::init_and_commit(project_dir, existing, &message) {
+        warn(e.to_string());
+    }
+}
+
+/// Fill in the custom placeholders declared by the template's manifest that
+/// weren't supplied already: ask for each in an interactive terminal, and use
+/// its default otherwise. `fallbacks` (the user config's values) stand in for
+/// a missing default. Fails, before anything is written, when a placeholder has neither a value
+/// nor a default in a non-interactive run.
+fn resolve_declared(
+    declared: &[DeclaredPlaceholder],
+    placeholders: &mut HashMap<String, String>,
+    fallbacks: &HashMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    let interactive = is_interactive();
+
+    for placeholder in declared {
+        if placeholders.contains_key(&placeholder.key) {
+            continue;
+        }
+
+        let default = placeholder
+            .default
+            .as_ref()
+            .or_else(|| fallbacks.get(&placeholder.key));
+        let value = if interactive {
+            let mut input = Input::<String>::new()
+                .with_prompt(placeholder.prompt.as_deref().unwrap_or(&placeholder.key));
+            if let Some(default) = default {
+                input = input.default(default.clone());
+            }
+            input.interact_text()?
+        } else {
+            default.cloned().ok_or_else(|| {
+                CliError::localized(
+                    "missing_placeholder",
+                    "error.missing-placeholder",
+                    &[
+                        ("token", &format!("{{{{{}}}}}", placeholder.key)),
+                        ("key", &placeholder.key),
+                    ],
+                )
+            })?
+        };
+        placeholders.insert(placeholder.key.clone(), value);
+    }
+    Ok(())
+}
+
+/// The user config's `vars`, less those `declared` by the template: for
+/// those it only stands in for a missing default (see [`resolve_declared`]).
+fn config_vars(
+    user_config: &config::Config,
+    declared: &[DeclaredPlaceholder],
+) -> HashMap<String, String> {
+    user_config
+        .vars
+        .iter()
+        .filter(|(key, _)| !declared.iter().any(|p| &p.key == *key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Fail unless every feature in `requested` is declared by the manifest of
+/// `template`.
+fn check_features(
+    template: &str,
+    declared: &[Feature],
+    requested: &[String],
+) -> Result<(), Box<dyn Error>> {
+    for name in requested {
+        if !declared.iter().any(|f| &f.name == name) {
+            let available: Vec<&str> = declared.iter().map(|f| f.name.as_str()).collect();
+            let error = if available.is_empty() {
+                CliError::localized(
+                    "unknown_feature",
+                    "error.unknown-feature-none",
+                    &[("feature", name), ("template", &template)],
+                )
+            } else {
+                CliError::localized(
+                    "unknown_feature",
+                    "error.unknown-feature",
+                    &[
+                        ("feature", name),
+                        ("template", &template),
+                        ("available", &available.join(", ")),
+                    ],
+                )
+            };
+            return Err(error.into());
+        }
+    }
+    Ok(())
+}
+
+/// The template features to enable: those `requested` with `--feature`, then
+/// for each other declared feature, the answer to its question in an
+/// interactive terminal or its default otherwise.
+fn resolve_features(
+    template: &str,
+    declared: &[Feature],
+    requested: &[String],
+) -> Result<BTreeSet<String>, Box<dyn Error>> {
+    check_features(template, declared, requested)?;
+    let interactive = is_interactive();
+
+    let mut enabled: BTreeSet<String> = requested.iter().cloned().collect();
+    for feature in declared {
+        if enabled.contains(&feature.name) {
+            continue;
+        }
+        let enable = if interactive {
+            let prompt = match &feature.prompt {
+                Some(prompt) => prompt.clone(),
+                None => messages::fill("prompt.include-feature", &[("feature", &feature.name)]),
+            };
+            Confirm::new()
+                .with_prompt(prompt)
+                .default(feature.default)
+                .interact()?
+        } else {
+            feature.default
+        };
+        if enable {
+            enabled.insert(feature.name.clone());
+        }
+    }
+    Ok(enabled)
+}
+
+/// Where an effective setting came from.
+#[derive(Clone, Copy)]
+enum Origin {
+    CommandLine,
+    SpecFile,
+    UserConfig,
+    /// A built-in placeholder, such as `author` or `year`.
+    BuiltIn,
+    Default,
+}
+
+/// Origins of the settings that can come from either a flag or the spec file.
+struct Origins {
+    template: Origin,
+    name: Origin,
+    output_dir: Origin,
+    project_version: Origin,
+}
+
+/// Merge a command-line value over a spec-file value, recording which won.
+fn merge<T>(flag: Option<T>, spec: Option<T>) -> (Option<T>, Origin) {
+    match (flag, spec) {
+        (Some(v), _) => (Some(v), Origin::CommandLine),
+        (None, Some(v)) => (Some(v), Origin::SpecFile),
+        (None, None) => (None, Origin::Default),
+    }
+}
+
+/// The placeholder values for a run, each with where it came from. Later
+/// sources win: the built-ins, then the user config's `[vars]`, then the spec
+/// file's, then `--project-version` and finally `--set`, so the spec's values
+/// act as if they had been given as flags.
+fn merge_placeholders(
+    builtins: HashMap<String, String>,
+    config: &HashMap<String, String>,
+    vars: &HashMap<String, String>,
+    project_version: Option<&str>,
+    set: &[(String, String)],
+) -> BTreeMap<String, (String, Origin)> {
+    let mut merged: BTreeMap<String, (String, Origin)> = builtins
+        .into_iter()
+        .map(|(key, value)| (key, (value, Origin::BuiltIn)))
+        .collect();
+    merged.extend(
+        config
+            .iter()
+            .map(|(key, value)| (key.clone(), (value.clone(), Origin::UserConfig))),
+    );
+    merged.extend(
+        vars.iter()
+            .map(|(key, value)| (key.clone(), (value.clone(), Origin::SpecFile))),
+    );
+    if let Some(version) = project_version {
+        merged.insert(
+            "version".to_string(),
+            (version.to_string(), Origin::CommandLine),
+        );
+    }
+    merged.extend(
+        set.iter()
+            .map(|(key, value)| (key.clone(), (value.clone(), Origin::CommandLine))),
+    );
+    merged
+}
+
+/// Print the effective configuration for `--print-config`, one setting per
+/// line with its origin, or as JSON. The placeholder values are merged as a
+/// run would merge them, except for those that depend on the template chosen:
+/// its declared defaults, and `template-source` (shown as `templates`).
+/// Environment values are not echoed.
+fn print_config(
+    args: &Cli,
+    origins: &Origins,
+    vars: &HashMap<String, String>,
+    user_config: &config::Config,
+    source: &TemplateSource,
+    json: bool,
+) {
+    let spec_origin = match &args.spec {
+        Some(path) => format!("spec file {}", path.display()),
+        None => "spec file".to_string(),
+    };
+    let config_origin = match config::path() {
+        Some(path) => format!("user config {}", path.display()),
+        None => "user config".to_string(),
+    };
+    let describe = |origin: Origin, default: &str| match origin {
+        Origin::CommandLine => "command line".to_string(),
+        Origin::SpecFile => spec_origin.clone(),
+        Origin::UserConfig => config_origin.clone(),
+        Origin::BuiltIn => "built-in".to_string(),
+        Origin::Default => default.to_string(),
+    };
+
+    let cwd = env::current_dir().map(|d| d.display().to_string());
+    let (templates, templates_origin) = match source {
+        _ if args.from_git.is_some() => (
+            args.from_git.clone().unwrap_or_default(),
+            "command line --from-git",
+        ),
+        _ if args.from_archive.is_some() => (
+            args.from_archive
+                .as_deref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            "command line --from-archive",
+        ),
+        TemplateSource::Filesystem(root) => (root.display().to_string(), "discovered on disk"),
+        TemplateSource::Embedded => ("embedded".to_string(), "fallback: none found on disk"),
+    };
+    let mtime = if env::var_os("SOURCE_DATE_EPOCH").is_some() {
+        (
+            "fixed",
+            "environment SOURCE_DATE_EPOCH; value redacted".to_string(),
+        )
+    } else if args.deterministic {
+        ("fixed (Unix epoch)", "command line".to_string())
+    } else {
+        ("unchanged", "default".to_string())
+    };
+    let settings = vec![
+        (
+            "template".to_string(),
+            args.template
+                .clone()
+                .unwrap_or_else(|| "<interactive>".to_string()),
+            describe(origins.template, "unset; chosen from a menu"),
+        ),
+        (
+            "name".to_string(),
+            args.name.clone().unwrap_or_else(|| "<unset>".to_string()),
+            describe(origins.name, "unset"),
+        ),
+        (
+            "output_dir".to_string(),
+            args.output_dir
+                .as_ref()
+                .map(|d| d.display().to_string())
+                .unwrap_or_else(|| cwd.unwrap_or_default()),
+            describe(origins.output_dir, "default: current directory"),
+        ),
+        (
+            "project_version".to_string(),
+            args.project_version.clone(),
+            describe(origins.project_version, "default"),
+        ),
+        (
+            "templates".to_string(),
+            templates,
+            templates_origin.to_string(),
+        ),
+        ("mtime".to_string(), mtime.0.to_string(), mtime.1),
+    ];
+
+    // Without a name there are no name-derived placeholders to show.
+    let mut builtins = build_placeholders(args.name.as_deref().unwrap_or_default());
+    if args.name.is_none() {
+        builtins.retain(|key, _| !key.starts_with("project-name"));
+    }
+    let project_version = matches!(origins.project_version, Origin::CommandLine)
+        .then_some(args.project_version.as_str());
+    let merged = merge_placeholders(
+        builtins,
+        &user_config.vars,
+        vars,
+        project_version,
+        &args.set,
+    );
+    let vars: Vec<(String, String, String)> = merged
+        .into_iter()
+        .map(|(key, (value, origin))| (key, value, describe(origin, "default")))
+        .collect();
+
+    if json {
+        println!("{:#}", reporter::config_json(&settings, &vars));
+        return;
+    }
+
+    let bold = Style::new().bold();
+    let dim = Style::new().dim();
+    println!("{} Effective configuration", bold.apply_to("=>"));
+    let width = vars
+        .iter()
+        .map(|(key, _, _)| key.len() + "var ".len())
+        .max()
+        .unwrap_or(0)
+        .max(16);
+    let line = |key: &str, value: &str, origin: &str| {
+        println!(
+            "  {key:<width$} {value:<32} {}",
+            dim.apply_to(format!("({origin})"))
+        );
+    };
+    for (key, value, origin) in &settings {
+        line(key, value, origin);
+    }
+    for (key, value, origin) in &vars {
+        line(&format!("var {key}"), value, origin);
+    }
+}
+
+/// Write the `--completions` script for `shell` to stdout. Template names
+/// can't be known statically, so those discovered now (on the filesystem, or
+/// embedded) are baked in as the candidates for `--template`.
+fn print_completions(shell: Shell) -> io::Result<()> {
+    let templates = match find_templates_root() {
+        Some(root) => list_templates(&root),
+        None => list_templates_embedded(&EMBEDDED_TEMPLATES),
+    };
+    let ids: Vec<String> = templates
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+
+    let mut command = Cli::command();
+    if !ids.is_empty() {
+        command = command.mut_arg("template", |arg| {
+            arg.value_parser(PossibleValuesParser::new(ids))
+        });
+    }
+    // Generated into a buffer first: writing straight to a closed pipe would
+    // panic inside `generate`.
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, "vibe-generate", &mut script);
+    io::stdout().write_all(&script)
+}
+
+/// Let the user edit the generated files matching `patterns` before the run
+/// finishes. Skipped with a warning when there is no terminal or editor.
+fn review_files(
+    project_dir: &Path,
+    patterns: &GlobSet,
+    editor_override: Option<&str>,
+) -> io::Result<()> {
+    let yellow = Style::new().yellow().bold();
+
+    if !is_interactive() {
+        eprintln!(
+            "{} Skipping --review in a non-interactive environment",
+            yellow.apply_to(messages::get("label.warning"))
+        );
+        return Ok(());
+    }
+
+    let Some(mut editor) = editor::resolve(editor_override) else {
+        eprintln!(
+            "{} No editor configured; set $VISUAL or $EDITOR, or pass --editor",
+            yellow.apply_to(messages::get("label.warning"))
+        );
+        return Ok(());
+    };
+    // The VS Code launcher returns immediately unless told to wait.
+    if editor == "code" {
+        editor.push_str(" --wait");
+    }
+
+    let reviewed = editor::review(project_dir, patterns, &editor)?;
+    if reviewed.is_empty() {
+        eprintln!(
+            "{} No generated files matched --review",
+            yellow.apply_to(messages::get("label.warning"))
+        );
+    }
+
+    Ok(())
+}
+
+fn run(matches: ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut args = Cli::from_arg_matches(&matches)?;
+    let json = args.format == OutputFormat::Json;
+    let lang = match &args.command {
+        Some(Command::Update(update)) => update.lang.as_deref(),
+        None => args.lang.as_deref(),
+    };
+    if !messages::init(lang) && lang.is_some() {
+        eprintln!(
+            "{} Unsupported --lang; using English. Supported: {}",
+            Style::new()
+                .yellow()
+                .bold()
+                .apply_to(messages::get("label.warning")),
+            messages::languages().join(", ")
+        );
+    }
+
+    if let Some(Command::Update(update)) = &args.command {
+        return update::run(update);
+    }
+
+    if let Some(shell) = args.completions {
+        print_completions(shell)?;
+        return Ok(());
+    }
+
+    if let Some(template) = &args.checksum {
+        let dir = EMBEDDED_TEMPLATES
+            .get_dir(template)
+            .ok_or_else(|| ScaffoldError::TemplateNotFound(template.clone()))?;
+        let checksum = template_checksum(dir);
+        if json {
+            println!(
+                "{:#}",
+                serde_json::json!({ "template": template, "checksum": checksum })
+            );
+        } else {
+            println!("{checksum}  {template}");
+        }
+        return Ok(());
+    }
 
     let bold = Style::new().bold();
     let green = Style::new().green().bold();
     let red = Style::new().red().bold();
+    let yellow = Style::new().yellow().bold();
+
+    // Fill in anything not given on the command line from the spec file.
+    let spec = match &args.spec {
+        Some(path) => spec::load(path).map_err(|e| CliError::new("invalid_spec", e.to_string()))?,
+        None => spec::Spec::default(),
+    };
+    let (template, template_origin) = merge(args.template.take(), spec.template);
+    let (name, name_origin) = merge(args.name.take(), spec.name);
+    let mut user_config = config::load();
+    let (output_dir, output_dir_origin) = match merge(args.output_dir.take(), spec.output_dir) {
+        (None, _) if user_config.output_dir.is_some() => {
+            (user_config.output_dir.take(), Origin::UserConfig)
+        }
+        merged => merged,
+    };
+    (args.template, args.name, args.output_dir) = (template, name, output_dir);
+    let vars = spec.vars;
+    if let (Some(path), Some(version)) = (&args.spec, vars.get("version")) {
+        cli::parse_semver(version).map_err(|e| {
+            CliError::new("invalid_spec", format!("Spec file {}: {e}", path.display()))
+        })?;
+    }
+    let origins = Origins {
+        template: template_origin,
+        name: name_origin,
+        output_dir: output_dir_origin,
+        project_version: match matches.value_source("project_version") {
+            Some(ValueSource::CommandLine) => Origin::CommandLine,
+            _ => Origin::Default,
+        },
+    };
+
+    if let Some(path) = args.spec.as_ref().filter(|_| !args.print_config) {
+        for (field, present) in [
+            ("template", args.template.is_some()),
+            ("name", args.name.is_some()),
+        ] {
+            if !present {
+                return Err(CliError::new(
+                    "invalid_spec",
+                    format!(
+                        "Spec file {} does not set `{field}` and --{field} was not given",
+                        path.display()
+                    ),
+                )
+                .into());
+            }
+        }
+    }
+
+    // The name becomes a directory and is substituted into the template, so
+    // reject unusable ones before touching the filesystem.
+    if let Some(name) = &args.name {
+        validate_project_name(name)?;
+    }
+
+    // The configuration is reported without fetching a remote template.
+    if args.print_config {
+        let source = match find_templates_root() {
+            Some(root) => TemplateSource::Filesystem(root),
+            None => TemplateSource::Embedded,
+        };
+        print_config(&args, &origins, &vars, &user_config, &source, json);
+        return Ok(());
+    }
+
+    // A remote source is explained from the flags alone, before anything is
+    // fetched.
+    if args.explain_source {
+        if let Some(explanation) = explain_fetch(&args) {
+            print_explanation(&explanation, json);
+            return Ok(());
+        }
+    }
+
+    // `--from-git` clones, and `--from-archive` extracts, into a temporary
+    // directory that is removed when it goes out of scope at the end of the
+    // run. The fetched template is then handled exactly like a local one: its
+    // parent directory is the templates root and its directory name the
+    // template name.
+    let fetch = match (&args.from_git, &args.from_archive) {
+        (Some(spec), _) => {
+            let temp = tempfile::tempdir()?;
+            let template_dir = clone_template(spec, args.template.as_deref(), temp.path())?;
+            Some((temp, template_dir, format!("git:{spec}")))
+        }
+        (None, Some(path)) => {
+            let temp = tempfile::tempdir()?;
+            let template_dir = extract_template(path, args.template.as_deref(), temp.path())?;
+            Some((temp, template_dir, format!("archive:{}", path.display())))
+        }
+        (None, None) => None,
+    };
+    let fetched = match fetch {
+        Some((temp, template_dir, origin)) => {
+            let name = template_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let root = template_dir
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            let provenance = match &args.template {
+                Some(subpath) => format!("{origin} ({subpath})"),
+                None => origin,
+            };
+            args.template = Some(name);
+            Some((temp, root, provenance))
+        }
+        None => None,
+    };
 
     // Prefer filesystem templates (local dev), fall back to embedded.
-    let source = match find_templates_root() {
-        Some(root) => TemplateSource::Filesystem(root),
-        None => TemplateSource::Embedded,
+    let source = match (&fetched, find_templates_root()) {
+        (Some((_, root, _)), _) => TemplateSource::Filesystem(root.clone()),
+        (None, Some(root)) => TemplateSource::Filesystem(root),
+        (None, None) if args.no_embedded => {
+            return Err(CliError::new(
+                "no_templates_directory",
+                "No templates directory found on the filesystem and --no-embedded is set",
+            )
+            .into());
+        }
+        (None, None) => TemplateSource::Embedded,
     };
 
     // Discover available templates.
-    let available = match &source {
+    let templates = match &source {
         TemplateSource::Filesystem(root) => list_templates(root)?,
-        TemplateSource::Embedded => list_templates_embedded(&EMBEDDED_TEMPLATES),
+        TemplateSource::Embedded => list_templates_embedded(&EMBEDDED_TEMPLATES)?,
     };
+    let available: Vec<String> = templates.iter().map(|t| t.id.clone()).collect();
 
     if available.is_empty() {
-        eprintln!("{} No templates found", red.apply_to("Error:"));
-        process::exit(1);
+        return Err(CliError::localized("no_templates", "error.no-templates", &[]).into());
     }
 
+    // `--tag` narrows what is offered; `--template` can still name any.
+    let offered = filter_by_tag(&templates, args.tag.as_deref())?;
+
+    if args.list && json {
+        println!("{:#}", reporter::templates_json(&offered));
+        return Ok(());
+    }
+    if args.list {
+        let dim = Style::new().dim();
+        let width = offered.iter().map(|t| t.id.len()).max().unwrap_or(0);
+        for template in &offered {
+            match &template.manifest.description {
+                Some(description) => {
+                    println!("{:<width$}  {}", template.id, dim.apply_to(description))
+                }
+                None => println!("{}", template.id),
+            }
+        }
+        return Ok(());
+    }
+
+    if args.explain_source {
+        let value = args.template.as_deref().unwrap_or_default();
+        print_explanation(&explain_source(value, &source, &available), json);
+        return Ok(());
+    }
+
+    let project_name = args.name.clone().expect("clap requires --name");
+
     // Pick a template — either from the CLI flag or via interactive selection.
+    let mut picked = false;
     let template_name = match args.template {
         Some(t) => {
             if !available.contains(&t) {
-                eprintln!(
-                    "{} Unknown template \"{}\". Available: {}",
-                    red.apply_to("Error:"),
-                    t,
-                    available.join(", ")
-                );
-                process::exit(1);
+                let list = available.join(", ");
+                let explained = match suggest::closest(&t, &available) {
+                    Some(suggestion) => CliError::localized(
+                        "template_not_found",
+                        "error.unknown-template-suggest",
+                        &[
+                            ("template", &t),
+                            ("suggestion", &suggestion),
+                            ("available", &list),
+                        ],
+                    ),
+                    None => CliError::localized(
+                        "template_not_found",
+                        "error.unknown-template",
+                        &[("template", &t), ("available", &list)],
+                    ),
+                };
+                return Err(explained
+                    .caused_by(ScaffoldError::TemplateNotFound(t))
+                    .into());
             }
             t
         }
         None => {
-            let selection = Select::new()
-                .with_prompt("Select a template")
-                .items(&available)
-                .default(0)
-                .interact()?;
-            available[selection].clone()
+            // A `--tag` already picked the category.
+            picked = true;
+            select_template(&offered, args.tag.is_none())?
         }
     };
 
-    let output_dir = args
-        .output_dir
-        .unwrap_or_else(|| env::current_dir().expect("cannot determine current directory"));
+    if let Some(language) = &args.gitignore {
+        if gitignore::snippet(language).is_none() {
+            return Err(CliError::new(
+                "unsupported_gitignore_language",
+                format!(
+                    "Unsupported .gitignore language \"{}\". Supported: {}",
+                    language,
+                    gitignore::LANGUAGES.join(", ")
+                ),
+            )
+            .into());
+        }
+    }
 
-    println!(
-        "{} Scaffolding project {} from template {}...",
+    let mut review = GlobSetBuilder::new();
+    for pattern in &args.review {
+        review.add(Glob::new(pattern).map_err(|e| {
+            CliError::new("invalid_review_glob", format!("Invalid --review glob: {e}"))
+        })?);
+    }
+    let review = review.build()?;
+
+    // `--to-stdout` scaffolds into a staging directory that is removed when
+    // it goes out of scope, after the tar stream has been written.
+    let staging = if args.to_stdout {
+        if json {
+            return Err(CliError::new(
+                "conflicting_output",
+                "--format json cannot be combined with --to-stdout, \
+                 which writes the tar stream to stdout",
+            )
+            .into());
+        }
+        if Term::stdout().is_term() {
+            return Err(CliError::new(
+                "stdout_is_terminal",
+                "Refusing to write a tar stream to a terminal; redirect or pipe stdout",
+            )
+            .into());
+        }
+        Some(tempfile::tempdir()?)
+    } else {
+        None
+    };
+
+    let output_dir = match &staging {
+        Some(dir) => dir.path().to_path_buf(),
+        None => args
+            .output_dir
+            .unwrap_or_else(|| env::current_dir().expect("cannot determine current directory")),
+    };
+
+    let status = format!(
+        "{} {}",
         bold.apply_to("=>"),
-        green.apply_to(&args.name),
-        green.apply_to(&template_name),
+        messages::fill(
+            "status.scaffolding",
+            &[
+                ("name", &green.apply_to(&project_name)),
+                ("template", &green.apply_to(&template_name)),
+            ]
+        ),
     );
+    if args.to_stdout {
+        eprintln!("{status}");
+    } else if !json {
+        println!("{status}");
+    }
+
+    let mtime = reproducible_mtime(args.deterministic)?;
+
+    // Provenance: where the template came from.
+    let template_source = match &fetched {
+        Some((_, _, provenance)) => provenance.clone(),
+        None => source.provenance(&template_name),
+    };
+
+    let mut builtins = build_placeholders(&project_name);
+    builtins.insert("template-source".to_string(), template_source);
+    // `--project-version` only counts when given; its default is a built-in.
+    let project_version = matches!(origins.project_version, Origin::CommandLine)
+        .then_some(args.project_version.as_str());
+    let manifest = templates
+        .iter()
+        .find(|t| t.id == template_name)
+        .map(|t| t.manifest.clone())
+        .unwrap_or_default();
+    let mut placeholders: HashMap<String, String> = merge_placeholders(
+        builtins,
+        &config_vars(&user_config, &manifest.placeholders),
+        &vars,
+        project_version,
+        &args.set,
+    )
+    .into_iter()
+    .map(|(key, (value, _))| (key, value))
+    .collect();
+    resolve_declared(&manifest.placeholders, &mut placeholders, &user_config.vars)?;
+    let features = resolve_features(&template_name, &manifest.features, &args.features)?;
+
+    if manifest.tags.iter().any(|tag| tag == "rust") {
+        if let Some(problem) = crate_name_problem(&project_name) {
+            eprintln!(
+                "{} {}",
+                yellow.apply_to(messages::get("label.warning")),
+                messages::fill(
+                    "warning.invalid-crate-name",
+                    &[("name", &project_name), ("problem", &problem)]
+                ),
+            );
+        }
+    }
+
+    if args.secret_seed.is_some() {
+        eprintln!(
+            "{} {}",
+            red.apply_to(messages::get("label.warning")),
+            messages::get("warning.secret-seed"),
+        );
+    }
+
+    let options = ScaffoldOptions {
+        project_name: project_name.clone(),
+        output_dir: output_dir.clone(),
+        placeholders,
+        force: args.force,
+        max_substitutions: args.max_substitutions,
+        secret_seed: args.secret_seed,
+        strip_keep_files: args.strip_keep_files,
+        strict: args.strict,
+        features,
+    };
 
-    match &source {
+    let plan = match &source {
         TemplateSource::Filesystem(root) => {
             let template_dir = resolve_template_dir(root, &template_name);
-            scaffold(&template_dir, &output_dir, &args.name)?;
+            plan(&template_dir, &options)?
+        }
+        TemplateSource::Embedded => plan_embedded(&EMBEDDED_TEMPLATES, &template_name, &options)?,
+    };
+
+    // Preview what an interactively picked template will create before
+    // committing to it. Stdout is reserved for the result in JSON mode, and
+    // for the tar stream with `--to-stdout`.
+    if picked && !args.dry_run {
+        let preview =
+            reporter::render_preview(&template_name, &manifest, &options.placeholders, &plan);
+        if json || args.to_stdout {
+            eprint!("{preview}");
+        } else {
+            print!("{preview}");
         }
-        TemplateSource::Embedded => {
-            scaffold_embedded(&EMBEDDED_TEMPLATES, &template_name, &output_dir, &args.name)?;
+
+        let proceed = Confirm::new()
+            .with_prompt(messages::get("prompt.proceed"))
+            .default(true)
+            .interact()?;
+        if !proceed {
+            if json || args.to_stdout {
+                eprintln!("{}", messages::get("status.aborted"));
+            } else {
+                println!("{}", messages::get("status.aborted"));
+            }
+            return Ok(());
         }
     }
 
+    if args.dry_run {
+        let setup_commands = if args.no_hooks {
+            &[][..]
+        } else {
+            &manifest.post_setup[..]
+        };
+        if json {
+            println!(
+                "{:#}",
+                reporter::plan_json(&template_name, &plan, setup_commands)
+            );
+            return Ok(());
+        }
+        print!("{}", reporter::render_plan(&plan, true));
+        print_unresolved(&plan.unresolved, false);
+        if !setup_commands.is_empty() {
+            println!("\n  Setup commands that would run:");
+            for command in setup_commands {
+                println!("    {command}");
+            }
+        }
+        return Ok(());
+    }
+
+    let bar = ProgressBar::new(0).with_style(
+        ProgressStyle::with_template("  [{bar:30}] {pos}/{len} {wide_msg}")?.progress_chars("=> "),
+    );
+    // `--verbose` logs go to stderr, around the progress bar. Directories
+    // are created before any file, links after all of them.
+    let log = |line: String| bar.suspend(|| eprintln!("{line}"));
+    if args.verbose {
+        for dir in &plan.dirs {
+            log(reporter::verbose_line(&plan, "mkdir", dir));
+        }
+    }
+    let mut progress = |done: usize, total: usize, path: &Path| {
+        if args.verbose {
+            log(reporter::verbose_line(&plan, "write", path));
+        }
+        bar.set_length(total as u64);
+        bar.set_position(done as u64);
+        bar.set_message(path.display().to_string());
+    };
+    let report = plan.execute(&mut progress)?;
+    bar.finish_and_clear();
+    if args.verbose {
+        for link in &plan.links {
+            if !report.skipped_links.contains(&link.path) {
+                log(reporter::verbose_line(&plan, "link", &link.path));
+            }
+        }
+        log(reporter::verbose_summary(&plan, &report));
+    }
+
+    if let Some(language) = &args.gitignore {
+        gitignore::apply(&output_dir.join(&project_name), language)?;
+    }
+
+    // Recorded with the `.gitignore` merge, which is the tool's own and which
+    // `update` repeats, but before `--review`: edits made there are the
+    // user's, so `update` leaves those files alone like any other change.
+    if !args.no_manifest {
+        let project_dir = output_dir.join(&project_name);
+        let mut recorded = ProjectManifest::record(
+            &project_dir,
+            &template_name,
+            &options,
+            mtime.unwrap_or_else(SystemTime::now),
+            report.files.iter().map(|f| f.path.as_path()),
+        )?;
+        recorded.gitignore = args.gitignore.clone();
+        recorded.write(&project_dir)?;
+    }
+
+    if !args.review.is_empty() {
+        review_files(
+            &output_dir.join(&project_name),
+            &review,
+            args.editor.as_deref(),
+        )?;
+    }
+
+    let hooks_ok = if manifest.post_setup.is_empty() {
+        true
+    } else if args.no_hooks {
+        eprintln!("{}", messages::get("status.hooks-skipped"));
+        true
+    } else {
+        run_hooks(
+            &manifest.post_setup,
+            &output_dir.join(&project_name),
+            args.to_stdout || json,
+        )?
+    };
+
+    if let Some(mtime) = mtime {
+        set_mtimes(&output_dir.join(&project_name), mtime)?;
+    }
+
+    if args.git {
+        init_git(&output_dir.join(&project_name), &template_name);
+    }
+
+    if args.to_stdout {
+        archive::write_tar(
+            &output_dir.join(&project_name),
+            &project_name,
+            io::stdout().lock(),
+        )?;
+        eprintln!(
+            "\n{} {}",
+            green.apply_to(messages::get("label.success")),
+            messages::fill(
+                "status.streamed",
+                &[("name", &bold.apply_to(&project_name))]
+            ),
+        );
+        print_unresolved(&report.unresolved, true);
+        if !hooks_ok {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if json {
+        let dest = output_dir.join(&project_name);
+        println!(
+            "{:#}",
+            reporter::report_json(&template_name, &dest, &report, hooks_ok)
+        );
+        if !hooks_ok {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let project_path = format!("{}/{}", output_dir.display(), &project_name);
     println!(
-        "\n{} Project {} created at {}/{}",
-        green.apply_to("Success!"),
-        bold.apply_to(&args.name),
-        output_dir.display(),
-        &args.name,
+        "\n{} {}",
+        green.apply_to(messages::get("label.success")),
+        messages::fill(
+            "status.created",
+            &[
+                ("name", &bold.apply_to(&project_name)),
+                ("path", &project_path),
+                ("count", &report.substitutions),
+            ]
+        ),
     );
+    print_unresolved(&report.unresolved, false);
+    if !report.skipped_links.is_empty() {
+        println!(
+            "\n{} {}",
+            yellow.apply_to(messages::get("label.warning")),
+            messages::get("warning.links-skipped"),
+        );
+        for path in &report.skipped_links {
+            println!("  {}", path.display());
+        }
+    }
+    if !report.overwritten.is_empty() {
+        println!(
+            "\n{} {}",
+            yellow.apply_to(messages::get("label.warning")),
+            messages::fill(
+                "status.overwritten",
+                &[
+                    ("count", &report.overwritten.len()),
+                    ("path", &project_path)
+                ]
+            ),
+        );
+        for path in &report.overwritten {
+            println!("  {}", path.display());
+        }
+    }
+    // The tree is what the template created, not what hooks or `--git` added
+    // since.
+    if args.show_tree {
+        let mut files: Vec<PathBuf> = report.files.iter().map(|f| f.path.clone()).collect();
+        files.extend(
+            plan.links
+                .iter()
+                .filter(|l| !report.skipped_links.contains(&l.path))
+                .map(|l| l.path.clone()),
+        );
+        print!(
+            "\n{}",
+            reporter::render_tree(&project_name, &files, TREE_FILE_LIMIT)
+        );
+    }
+
     println!(
-        "\n  cd {}/{} && get started!",
-        output_dir.display(),
-        &args.name
+        "\n  {}",
+        messages::fill("status.next-steps", &[("path", &project_path)])
     );
 
+    if args.open {
+        open_in_editor(&output_dir.join(&project_name), args.editor.as_deref());
+    }
+
+    if !hooks_ok {
+        process::exit(1);
+    }
     Ok(())
 }
 
+/// Warn about the placeholders left as they were because nothing gave them a
+/// value, by file. `to_stderr` keeps the warning out of output on stdout.
+fn print_unresolved(unresolved: &BTreeMap<PathBuf, BTreeSet<String>>, to_stderr: bool) {
+    if unresolved.is_empty() {
+        return;
+    }
+    let mut warning = format!(
+        "\n{} {}\n",
+        Style::new()
+            .yellow()
+            .bold()
+            .apply_to(messages::get("label.warning")),
+        messages::get("warning.unresolved-placeholders"),
+    );
+    for (path, tokens) in unresolved {
+        let tokens: Vec<_> = tokens.iter().map(String::as_str).collect();
+        warning.push_str(&format!("  {}: {}\n", path.display(), tokens.join(", ")));
+    }
+    if to_stderr {
+        eprint!("{warning}");
+    } else {
+        print!("{warning}");
+    }
+}
+
+/// A suggestion for how to get past a scaffold failure, if there is an
+/// obvious one.
+fn hint(error: &ScaffoldError) -> Option<&'static str> {
+    match error {
+        ScaffoldError::DestinationExists(_) => {
+            Some("Pass --force to merge into it, or pick another --name or --output-dir.")
+        }
+        ScaffoldError::TemplateNotFound(_) => {
+            Some("Run without --template to choose from the available templates.")
+        }
+        ScaffoldError::TooManySubstitutions { .. } => {
+            Some("If you trust the template, raise the limit with --max-substitutions.")
+        }
+        ScaffoldError::UnresolvedPlaceholder { .. } => {
+            Some("Give it a value with --set KEY=VALUE, or drop --strict to only warn.")
+        }
+        ScaffoldError::CopyFailed { source, .. }
+            if source.kind() == io::ErrorKind::PermissionDenied =>
+        {
+            Some("Check that you can write to the output directory.")
+        }
+        _ => None,
+    }
+}
+
 fn main() {
-    if let Err(e) = run() {
+    let matches = Cli::command().get_matches();
+    let json = matches.get_one::<OutputFormat>("format") == Some(&OutputFormat::Json);
+
+    if let Err(e) = run(matches) {
+        if json {
+            println!("{:#}", reporter::error_json(e.as_ref()));
+            process::exit(1);
+        }
         let red = Style::new().red().bold();
-        eprintln!("{} {e}", red.apply_to("Error:"));
+        eprintln!("{} {e}", red.apply_to(messages::get("label.error")));
+        if let Some(hint) = scaffold_error(e.as_ref()).and_then(hint) {
+            eprintln!("  {hint}");
+        }
         process::exit(1);
     }
 }
@@ -0,0 +1,207 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use include_dir::Dir;
+use serde::Deserialize;
+
+use crate::scaffold::ScaffoldError;
+
+/// File name of the optional manifest at a template's root. It describes the
+/// template and is never copied into generated projects.
+pub const MANIFEST_FILE: &str = "template.toml";
+
+/// Placeholder delimiters used when a manifest doesn't declare its own.
+pub const DEFAULT_DELIMITERS: (&str, &str) = ("{{", "}}");
+
+/// Metadata from a template's `template.toml`:
+///
+/// ```toml
+/// name = "Rust workspace"
+/// description = "Multi-crate Cargo workspace with a CLI and a core library"
+/// tags = ["rust", "cli"]
+/// executable = ["scripts/*.sh", "gradlew"]
+/// post_setup = ["git init", "cargo build"]
+/// delimiters = ["<<", ">>"]
+///
+/// [[placeholders]]
+/// key = "db-url"
+/// prompt = "Database URL"
+/// default = "postgres://localhost/app"
+///
+/// [[features]]
+/// name = "docker"
+/// prompt = "Include a Dockerfile?"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Human-readable name; the directory name is used when absent.
+    pub name: Option<String>,
+    /// One-line description shown in the template menu.
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Custom placeholders the template uses, beyond the built-in ones.
+    #[serde(default)]
+    pub placeholders: Vec<DeclaredPlaceholder>,
+    /// Optional parts of the template, enabled with `--feature`.
+    #[serde(default)]
+    pub features: Vec<Feature>,
+    /// Globs, relative to the template root, of files to make executable
+    /// (mode `0o755`) on Unix. Needed for embedded templates, which carry no
+    /// permissions; filesystem templates also keep their own executable bits.
+    #[serde(default)]
+    pub executable: Vec<String>,
+    /// Shell commands to run in the new project after it is generated, in
+    /// order.
+    #[serde(default)]
+    pub post_setup: Vec<String>,
+    /// Opening and closing placeholder markers, for templates whose files
+    /// already use `{{ }}` for something else. See [`Manifest::delimiters`].
+    pub delimiters: Option<(String, String)>,
+    /// `executable`, compiled when the manifest is parsed.
+    #[serde(skip)]
+    executable_set: GlobSet,
+}
+
+impl Manifest {
+    /// Whether the template file at `rel` is listed in `executable`.
+    pub fn is_executable(&self, rel: &Path) -> bool {
+        self.executable_set.is_match(rel)
+    }
+
+    /// The declared delimiters, or [`DEFAULT_DELIMITERS`]. Placeholder keys
+    /// are the same either way: `<<project-name>>` instead of
+    /// `{{project-name}}`.
+    pub fn delimiters(&self) -> (&str, &str) {
+        match &self.delimiters {
+            Some((open, close)) => (open, close),
+            None => DEFAULT_DELIMITERS,
+        }
+    }
+}
+
+/// A custom placeholder declared in a manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredPlaceholder {
+    /// The key used in `{{key}}` tokens.
+    pub key: String,
+    pub description: Option<String>,
+    /// Question asked in interactive runs; the key is used when absent.
+    pub prompt: Option<String>,
+    /// Value used when the user accepts the suggestion, and in
+    /// non-interactive runs. Without one, a value must be supplied.
+    pub default: Option<String>,
+}
+
+/// An optional part of a template, declared in a manifest. Files and
+/// directories under a `__if_<name>__` directory are only generated when the
+/// feature is enabled, and the marker directory is left out of their paths.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Feature {
+    /// Name used in `__if_<name>__` markers and with `--feature`.
+    pub name: String,
+    pub description: Option<String>,
+    /// Yes/no question asked in interactive runs; `Include <name>?` when
+    /// absent.
+    pub prompt: Option<String>,
+    /// Whether the feature is enabled when not asked about: in
+    /// non-interactive runs, and as the suggested answer.
+    #[serde(default)]
+    pub default: bool,
+}
+
+/// An available template: its id, which is what `--template` selects, and
+/// its manifest (empty when the template has none). The id is the template's
+/// path relative to the templates root, with `/` between the names of any
+/// category directories (`rust/cli`).
+#[derive(Debug, Clone)]
+pub struct TemplateInfo {
+    pub id: String,
+    pub manifest: Manifest,
+}
+
+impl TemplateInfo {
+    /// The manifest name, or the id without one.
+    pub fn name(&self) -> &str {
+        self.manifest.name.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Menu label: `name — description`, or just the name.
+    pub fn label(&self) -> String {
+        match &self.manifest.description {
+            Some(description) => format!("{} — {description}", self.name()),
+            None => self.name().to_string(),
+        }
+    }
+}
+
+/// Parse manifest `contents`, naming `path` in any error.
+fn parse(contents: &str, path: &Path) -> Result<Manifest, ScaffoldError> {
+    let invalid = |message: String| ScaffoldError::InvalidManifest {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let mut manifest: Manifest =
+        toml::from_str(contents).map_err(|e| invalid(e.message().to_string()))?;
+
+    let mut executable = GlobSetBuilder::new();
+    for pattern in &manifest.executable {
+        executable.add(Glob::new(pattern).map_err(|e| invalid(e.to_string()))?);
+    }
+    manifest.executable_set = executable.build().map_err(|e| invalid(e.to_string()))?;
+
+    for feature in &manifest.features {
+        let valid = !feature.name.is_empty()
+            && feature
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(invalid(format!(
+                "feature name \"{}\" must be letters, digits, '-' and '_'",
+                feature.name
+            )));
+        }
+    }
+
+    if let Some((open, close)) = &manifest.delimiters {
+        if open.is_empty() || close.is_empty() {
+            return Err(invalid("delimiters must not be empty".to_string()));
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Load the manifest of the filesystem template at `template_dir`, or an
+/// empty one when it has none.
+pub fn load(template_dir: &Path) -> Result<Manifest, ScaffoldError> {
+    let path = template_dir.join(MANIFEST_FILE);
+    match fs::read_to_string(&path) {
+        Ok(contents) => parse(&contents, &path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Manifest::default()),
+        Err(source) => Err(ScaffoldError::ReadFailed { path, source }),
+    }
+}
+
+/// Load the manifest of an embedded template, or an empty one when it has
+/// none.
+pub fn load_embedded(embedded: &Dir, template_name: &str) -> Result<Manifest, ScaffoldError> {
+    let path = Path::new(template_name).join(MANIFEST_FILE);
+    let Some(file) = embedded.get_file(&path) else {
+        return Ok(Manifest::default());
+    };
+    let contents = file
+        .contents_utf8()
+        .ok_or_else(|| ScaffoldError::InvalidManifest {
+            path: path.clone(),
+            message: "not valid UTF-8".to_string(),
+        })?;
+    parse(contents, &path)
+}
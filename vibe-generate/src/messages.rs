@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Built-in English messages, keyed by a stable id. Used for any id the
+/// selected translation doesn't cover. `{name}` markers are filled by [`fill`].
+const ENGLISH: &[(&str, &str)] = &[
+    ("label.error", "Error:"),
+    ("label.warning", "Warning:"),
+    ("label.success", "Success!"),
+    ("label.unresolved", "unresolved:"),
+    ("error.no-templates", "No templates found"),
+    (
+        "error.unknown-template",
+        "Unknown template \"{template}\". Available: {available}",
+    ),
+    (
+        "error.unknown-template-suggest",
+        "Unknown template '{template}'. Did you mean '{suggestion}'?\n  Available: {available}",
+    ),
+    (
+        "error.missing-placeholder",
+        "No value for placeholder {token}; it has no default, so pass --set {key}=VALUE or \
+         run interactively",
+    ),
+    (
+        "error.unknown-feature",
+        "Unknown feature \"{feature}\" for template {template}. Available: {available}",
+    ),
+    (
+        "error.unknown-feature-none",
+        "Unknown feature \"{feature}\": template {template} declares none",
+    ),
+    (
+        "error.git-not-found",
+        "--from-git needs git, which was not found on PATH",
+    ),
+    ("error.clone-failed", "Could not clone {url}: {error}"),
+    (
+        "error.unsupported-archive",
+        "--from-archive {path} must be a .zip, .tar.gz or .tgz file",
+    ),
+    ("error.extract-failed", "Could not extract {path}: {error}"),
+    (
+        "error.invalid-template-path",
+        "--template \"{subpath}\" must be a relative path inside {origin}",
+    ),
+    (
+        "error.template-path-not-found",
+        "No directory \"{subpath}\" in {origin}",
+    ),
+    (
+        "error.no-tagged-templates",
+        "No templates are tagged \"{tag}\". Tags in use: {tags}",
+    ),
+    ("prompt.select-tag", "Select a category"),
+    ("label.uncategorized", "uncategorized"),
+    ("prompt.select-template", "Select a template"),
+    ("prompt.proceed", "Proceed with this template?"),
+    (
+        "preview.header",
+        "Template {template} will create {count} files:",
+    ),
+    ("preview.variables", "Variables:"),
+    ("status.aborted", "Aborted."),
+    (
+        "status.scaffolding",
+        "Scaffolding project {name} from template {template}...",
+    ),
+    (
+        "status.created",
+        "Project {name} created at {path} ({count} placeholders replaced)",
+    ),
+    (
+        "status.streamed",
+        "Project {name} written to stdout as a tar stream",
+    ),
+    (
+        "status.overwritten",
+        "Overwrote {count} existing files in {path}:",
+    ),
+    ("status.next-steps", "cd {path} && get started!"),
+    ("prompt.run-hooks", "Run these setup commands in {path}?"),
+    ("status.running-hook", "Running {command}"),
+    (
+        "status.hooks-skipped",
+        "Skipped the template's setup commands.",
+    ),
+    ("prompt.include-feature", "Include {feature}?"),
+    (
+        "prompt.git-commit-existing",
+        "The project is inside an existing git repository. Stage and commit it there?",
+    ),
+    (
+        "warning.git-existing-skipped",
+        "Already inside a git repository; skipped git init and the initial commit",
+    ),
+    (
+        "warning.git-not-found",
+        "git was not found on PATH; skipping --git",
+    ),
+    ("warning.git-failed", "Could not run git: {error}"),
+    ("warning.links-skipped", "Symbolic links are not supported on this platform; skipped:"),
+    ("warning.invalid-crate-name", "\"{name}\" is not a valid crate name: {problem}. The generated Cargo.toml will need editing."),
+    ("warning.secret-seed", "--secret-seed makes {{secret:N}} and {{password:N}} values predictable. NEVER use the generated secrets outside of tests."),
+    (
+        "warning.unresolved-placeholders",
+        "No value was given for these placeholders, so they were left as they are:",
+    ),
+    (
+        "status.updating",
+        "Updating {path} from template {template}",
+    ),
+    (
+        "status.updating-dry-run",
+        "Updating {path} from template {template} (dry run)",
+    ),
+    ("update.add", "add"),
+    ("update.update", "update"),
+    ("update.conflict", "conflict"),
+    ("update.keep", "keep"),
+    (
+        "status.update-summary",
+        "{added} added, {updated} updated, {unchanged} unchanged, {conflicts} conflicts \
+         (changed locally; skipped), {kept} kept (contain generated secrets)",
+    ),
+    (
+        "warning.update-no-manifest",
+        "{path} has no {manifest}, so every existing file that differs from the template \
+         is treated as changed and left alone",
+    ),
+    (
+        "warning.update-conflicts",
+        "Compare the conflicting files with the template and merge its changes by hand.",
+    ),
+    (
+        "error.update-not-a-project",
+        "{path} is not a directory; generate the project before updating it",
+    ),
+    (
+        "error.update-no-manifest",
+        "{path} has no {manifest} recording its template; pass --template",
+    ),
+    (
+        "error.hook-failed",
+        "Setup command `{command}` failed ({status}); the generated files were kept",
+    ),
+];
+
+/// Translations bundled into the binary, keyed by language code. Each is a
+/// TOML table mapping message ids to strings.
+const TRANSLATIONS: &[(&str, &str)] = &[("es", include_str!("locales/es.toml"))];
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Language codes with a bundled translation, plus the English default.
+pub fn languages() -> Vec<&'static str> {
+    let mut langs = vec!["en"];
+    langs.extend(TRANSLATIONS.iter().map(|(code, _)| *code));
+    langs
+}
+
+/// Select the catalog from `lang`, or from `$LANG` when not given.
+///
+/// Locale strings like `es_ES.UTF-8` are reduced to their language code.
+/// Unknown languages fall back to English. Only the first call has an effect.
+/// Returns whether the requested language is supported.
+pub fn init(lang: Option<&str>) -> bool {
+    let requested = lang
+        .map(str::to_string)
+        .or_else(|| env::var("LANG").ok())
+        .unwrap_or_default();
+    let code = requested
+        .split(['_', '.', '-'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let overrides = TRANSLATIONS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .and_then(|(_, source)| toml::from_str::<HashMap<String, String>>(source).ok())
+        .unwrap_or_default();
+
+    let _ = CATALOG.set(overrides);
+    languages().contains(&code.as_str())
+}
+
+/// Look up the message for `id` in the selected language.
+pub fn get(id: &str) -> String {
+    if let Some(message) = CATALOG.get().and_then(|c| c.get(id)) {
+        return message.clone();
+    }
+    english(id)
+}
+
+/// The built-in English message for `id`, whatever the selected language.
+fn english(id: &str) -> String {
+    ENGLISH
+        .iter()
+        .find(|(key, _)| *key == id)
+        .map(|(_, message)| message.to_string())
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Look up `id` and replace each `{key}` marker with its value from `args`.
+pub fn fill(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    replace(get(id), args)
+}
+
+/// Like [`fill`], but always in English, for machine-readable output.
+pub fn fill_english(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    replace(english(id), args)
+}
+
+fn replace(mut message: String, args: &[(&str, &dyn Display)]) -> String {
+    for (key, value) in args {
+        message = message.replace(&format!("{{{key}}}"), &value.to_string());
+    }
+    message
+}
@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::scaffold::{rfc3339, ScaffoldError, ScaffoldOptions};
+
+/// File name of the record written into generated projects.
+pub const PROJECT_MANIFEST_FILE: &str = ".vibe-manifest.toml";
+
+/// How a project was generated: the template, tool version and placeholder
+/// values used, and a SHA-256 of every file as generated, so a later update
+/// can reproduce the run and tell which files the user has changed since.
+///
+/// ```toml
+/// template = "rust-1.9"
+/// generator-version = "0.1.0"
+/// generated-at = "2026-10-14T09:30:00Z"
+/// features = ["docker"]
+/// gitignore = "rust"
+///
+/// [placeholders]
+/// project-name = "my-cli"
+/// version = "0.1.0"
+///
+/// [files]
+/// "Cargo.toml" = "5f2b…"
+/// "crates/core/src/lib.rs" = "9c1e…"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProjectManifest {
+    /// Template id, as given to `--template`.
+    pub template: String,
+    /// Version of vibe-generate that generated, or last updated, the project.
+    #[serde(default)]
+    pub generator_version: String,
+    /// When that was, as an RFC 3339 UTC timestamp.
+    #[serde(default)]
+    pub generated_at: String,
+    /// Template features that were enabled.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub features: BTreeSet<String>,
+    /// Language whose `.gitignore` entries were merged into the project
+    /// (`--gitignore`). The hashes include them, and `update` merges them in
+    /// again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gitignore: Option<String>,
+    /// Every placeholder value substituted. Generated secrets are not
+    /// placeholders and never appear here.
+    #[serde(default)]
+    pub placeholders: BTreeMap<String, String>,
+    /// Hex SHA-256 of each generated file, keyed by its `/`-separated path
+    /// relative to the project root.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl ProjectManifest {
+    /// Record a run of `template` with `options` at `generated_at`, and
+    /// `files` (relative to `project_dir`) as they are on disk now.
+    pub fn record<'a>(
+        project_dir: &Path,
+        template: &str,
+        options: &ScaffoldOptions,
+        generated_at: SystemTime,
+        files: impl IntoIterator<Item = &'a Path>,
+    ) -> Result<Self, ScaffoldError> {
+        let mut manifest = Self::default();
+        manifest.set_run(template, options, generated_at);
+        for rel in files {
+            manifest.update_hash(project_dir, rel)?;
+        }
+        Ok(manifest)
+    }
+
+    /// Replace the record of how the project was generated, keeping the file
+    /// hashes.
+    pub fn set_run(&mut self, template: &str, options: &ScaffoldOptions, generated_at: SystemTime) {
+        self.template = template.to_string();
+        self.generator_version = env!("CARGO_PKG_VERSION").to_string();
+        self.generated_at = rfc3339(generated_at);
+        self.features = options.features.clone();
+        self.placeholders = options
+            .placeholders
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+    }
+
+    /// Re-hash the file at `rel` (relative to `project_dir`) as it is on disk.
+    pub fn update_hash(&mut self, project_dir: &Path, rel: &Path) -> Result<(), ScaffoldError> {
+        let path = project_dir.join(rel);
+        let hash = hash_file(&path).map_err(|source| ScaffoldError::ReadFailed { path, source })?;
+        self.files.insert(key(rel), hash);
+        Ok(())
+    }
+
+    /// The recorded hash of the file at `rel`, if it was generated.
+    pub fn hash(&self, rel: &Path) -> Option<&str> {
+        self.files.get(&key(rel)).map(String::as_str)
+    }
+
+    /// Load the manifest of the project at `project_dir`, or `None` when it
+    /// has none.
+    pub fn load(project_dir: &Path) -> Result<Option<Self>, ScaffoldError> {
+        let path = project_dir.join(PROJECT_MANIFEST_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(ScaffoldError::ReadFailed { path, source }),
+        };
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| ScaffoldError::InvalidManifest {
+                path,
+                message: e.message().to_string(),
+            })
+    }
+
+    /// Write the manifest into the project at `project_dir`.
+    pub fn write(&self, project_dir: &Path) -> Result<(), ScaffoldError> {
+        let path = project_dir.join(PROJECT_MANIFEST_FILE);
+        let contents = toml::to_string(self).expect("manifest serializes to TOML");
+        fs::write(&path, contents).map_err(|source| ScaffoldError::CopyFailed { path, source })
+    }
+}
+
+/// The manifest key for `rel`: its components joined with `/` on every
+/// platform.
+fn key(rel: &Path) -> String {
+    let parts: Vec<_> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    parts.join("/")
+}
+
+/// Hex SHA-256 of the file at `path`, read in chunks.
+pub(crate) fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hex(hasher))
+}
+
+/// Hex digest of a finished hasher.
+pub(crate) fn hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
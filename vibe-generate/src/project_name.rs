@@ -0,0 +1,155 @@
+use crate::scaffold::ScaffoldError;
+
+/// Characters that can't appear in a directory name on at least one
+/// supported platform. Path separators are reported separately.
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '|', '?', '*'];
+
+/// Names Windows reserves for devices, with or without an extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Names Cargo refuses for a package: Rust keywords and the standard crates.
+const RESERVED_CRATE_NAMES: &[&str] = &[
+    "abstract",
+    "as",
+    "async",
+    "await",
+    "become",
+    "box",
+    "break",
+    "const",
+    "continue",
+    "crate",
+    "do",
+    "dyn",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "final",
+    "fn",
+    "for",
+    "if",
+    "impl",
+    "in",
+    "let",
+    "loop",
+    "macro",
+    "match",
+    "mod",
+    "move",
+    "mut",
+    "override",
+    "priv",
+    "pub",
+    "ref",
+    "return",
+    "self",
+    "Self",
+    "static",
+    "struct",
+    "super",
+    "trait",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "unsafe",
+    "unsized",
+    "use",
+    "virtual",
+    "where",
+    "while",
+    "yield",
+    "alloc",
+    "core",
+    "proc_macro",
+    "std",
+    "test",
+];
+
+fn invalid(name: &str, reason: String) -> ScaffoldError {
+    ScaffoldError::InvalidProjectName {
+        name: name.to_string(),
+        reason,
+    }
+}
+
+/// Check that `name` can be used as the project directory name: a single,
+/// non-hidden path component made of characters every platform accepts.
+///
+/// The error names the first offending character and its (1-based) position.
+pub fn validate_project_name(name: &str) -> Result<(), ScaffoldError> {
+    if name.trim().is_empty() {
+        return Err(invalid(name, "it is empty".to_string()));
+    }
+    if name.split(['/', '\\']).any(|part| part == "..") {
+        return Err(invalid(
+            name,
+            "\"..\" refers to the parent directory".to_string(),
+        ));
+    }
+    if name.starts_with('.') {
+        return Err(invalid(
+            name,
+            "it starts with '.', which would create a hidden directory".to_string(),
+        ));
+    }
+
+    for (position, c) in name.chars().enumerate() {
+        let position = position + 1;
+        let reason = if c == '/' || c == '\\' {
+            format!("'{c}' at position {position} is a path separator")
+        } else if c.is_control() {
+            format!(
+                "control character {:?} at position {position} is not allowed",
+                c
+            )
+        } else if ILLEGAL_CHARS.contains(&c) {
+            format!("'{c}' at position {position} is not allowed in directory names")
+        } else {
+            continue;
+        };
+        return Err(invalid(name, reason));
+    }
+
+    if name.ends_with(['.', ' ']) {
+        return Err(invalid(
+            name,
+            "it ends with a dot or space, which Windows drops from directory names".to_string(),
+        ));
+    }
+    let stem = name.split('.').next().unwrap_or(name);
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        return Err(invalid(
+            name,
+            format!("\"{stem}\" is a reserved device name on Windows"),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Why `name` would not be accepted by Cargo as a package name, if it
+/// wouldn't: Rust templates substitute it into `Cargo.toml`.
+pub fn crate_name_problem(name: &str) -> Option<String> {
+    if let Some((position, c)) = name
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+    {
+        return Some(format!(
+            "'{c}' at position {} is not allowed in crate names (use letters, digits, '-' and '_')",
+            position + 1
+        ));
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Some("crate names can't start with a digit".to_string());
+    }
+    if RESERVED_CRATE_NAMES.contains(&name.replace('-', "_").as_str()) {
+        return Some(format!("\"{name}\" is a reserved name in Rust"));
+    }
+    None
+}
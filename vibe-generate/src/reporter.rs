@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use console::Style;
+use serde_json::{json, Value};
+
+use vibe_generate::{Manifest, Plan, ScaffoldError, ScaffoldReport, TemplateInfo};
+
+use crate::error::{scaffold_error, CliError};
+use crate::{messages, Explanation};
+
+/// A directory node of the rendered tree; `None` children are files.
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<String, Option<Node>>,
+}
+
+impl Node {
+    fn insert(&mut self, path: &Path) {
+        let parts: Vec<String> = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let Some((file, dirs)) = parts.split_last() else {
+            return;
+        };
+
+        let mut node = self;
+        for dir in dirs {
+            node = node
+                .children
+                .entry(dir.clone())
+                .or_insert_with(|| Some(Node::default()))
+                .get_or_insert_with(Node::default);
+        }
+        node.children.entry(file.clone()).or_insert(None);
+    }
+}
+
+/// Render `files` (paths relative to the project root) as an indented tree
+/// under `root_name`, like the `tree` command.
+///
+/// Directories are listed before files at each level. At most `limit` files
+/// are shown; the rest are summarized in a trailing "... N more files" line.
+pub fn render_tree(root_name: &str, files: &[PathBuf], limit: usize) -> String {
+    let dir_style = Style::new().blue().bold();
+
+    let mut root = Node::default();
+    for file in files {
+        root.insert(file);
+    }
+
+    let mut out = format!("{}\n", dir_style.apply_to(format!("{root_name}/")));
+    let mut shown = 0;
+    render_node(&root, "", &dir_style, limit, &mut shown, &mut out);
+
+    if files.len() > shown {
+        out.push_str(&format!("... {} more files\n", files.len() - shown));
+    }
+    out
+}
+
+fn render_node(
+    node: &Node,
+    prefix: &str,
+    dir_style: &Style,
+    limit: usize,
+    shown: &mut usize,
+    out: &mut String,
+) {
+    // Directories first, then files, each group in name order.
+    let mut entries: Vec<(&String, &Option<Node>)> = node.children.iter().collect();
+    entries.sort_by_key(|(name, child)| (child.is_none(), name.as_str()));
+
+    for (i, (name, child)) in entries.iter().enumerate() {
+        if *shown >= limit {
+            return;
+        }
+
+        let last = i + 1 == entries.len();
+        let branch = if last { "└── " } else { "├── " };
+
+        match child {
+            Some(dir) => {
+                out.push_str(&format!(
+                    "{prefix}{branch}{}\n",
+                    dir_style.apply_to(format!("{name}/"))
+                ));
+                let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+                render_node(dir, &child_prefix, dir_style, limit, shown, out);
+            }
+            None => {
+                out.push_str(&format!("{prefix}{branch}{name}\n"));
+                *shown += 1;
+            }
+        }
+    }
+}
+
+/// Render a scaffold plan: each directory and file that would be created (or
+/// overwritten, with `--force`), with the placeholder keys substituted in each
+/// file. Values are not shown, so generated secrets never reach the terminal.
+///
+/// For `--dry-run` the listing is headed by the destination and ends by saying
+/// nothing was written; otherwise it is the body of [`render_preview`].
+pub fn render_plan(plan: &Plan, dry_run: bool) -> String {
+    let bold = Style::new().bold();
+    let dim = Style::new().dim();
+
+    let mut out = String::new();
+    if dry_run {
+        out.push_str(&format!(
+            "{} Dry run: would create {}\n",
+            bold.apply_to("=>"),
+            plan.dest.display()
+        ));
+    }
+
+    for dir in &plan.dirs {
+        out.push_str(&format!("  create dir   {}/\n", dir.display()));
+    }
+    for file in &plan.files {
+        let action = if plan.dest.join(&file.path).exists() {
+            "overwrite  "
+        } else {
+            "create file"
+        };
+        out.push_str(&format!("  {action}  {}", file.path.display()));
+        if !file.substitutions.is_empty() {
+            out.push_str(&format!(
+                "  {}",
+                dim.apply_to(substitution_keys(&file.substitutions))
+            ));
+        }
+        out.push('\n');
+    }
+
+    for link in &plan.links {
+        out.push_str(&format!(
+            "  create link  {} -> {}\n",
+            link.path.display(),
+            link.target.display()
+        ));
+    }
+
+    out.push_str(&format!(
+        "\n  {} directories, {} files, {} links, {} placeholder substitutions{}\n",
+        plan.dirs.len(),
+        plan.files.len(),
+        plan.links.len(),
+        plan.substitution_count(),
+        if dry_run { "; nothing was written" } else { "" }
+    ));
+    out
+}
+
+/// Render the preview shown after a template is picked interactively: its
+/// description, the values of the placeholders it declares, and `plan`.
+pub fn render_preview(
+    template: &str,
+    manifest: &Manifest,
+    placeholders: &HashMap<String, String>,
+    plan: &Plan,
+) -> String {
+    let bold = Style::new().bold();
+    let green = Style::new().green().bold();
+    let dim = Style::new().dim();
+
+    let mut out = format!(
+        "\n{} {}\n",
+        bold.apply_to("=>"),
+        messages::fill(
+            "preview.header",
+            &[
+                ("template", &green.apply_to(template)),
+                ("count", &plan.files.len())
+            ]
+        )
+    );
+    if let Some(description) = &manifest.description {
+        out.push_str(&format!("  {}\n", dim.apply_to(description)));
+    }
+    if !manifest.placeholders.is_empty() {
+        out.push_str(&format!("\n  {}\n", messages::get("preview.variables")));
+        let width = manifest
+            .placeholders
+            .iter()
+            .map(|p| p.key.len())
+            .max()
+            .unwrap_or(0);
+        for declared in &manifest.placeholders {
+            let value = placeholders.get(&declared.key).map_or("", String::as_str);
+            out.push_str(&format!("    {:<width$}  {value}\n", declared.key));
+        }
+    }
+    out.push('\n');
+    out.push_str(&render_plan(plan, false));
+    out
+}
+
+/// `{{key}} xN` for each substituted key, comma-separated.
+fn substitution_keys(substitutions: &BTreeMap<String, usize>) -> String {
+    let keys: Vec<String> = substitutions
+        .iter()
+        .map(|(key, count)| format!("{{{{{key}}}}} x{count}"))
+        .collect();
+    keys.join(", ")
+}
+
+/// `--verbose` log line for an entry of `plan` at `path`: `action`, the path
+/// (and target, for a link), the template path it was rendered from if its
+/// name had placeholders, and the substitutions made in it when it is a file.
+pub fn verbose_line(plan: &Plan, action: &str, path: &Path) -> String {
+    let dim = Style::new().dim();
+
+    let mut line = format!("  {action:<6} {}", path.display());
+    if let Some(link) = plan.links.iter().find(|l| l.path == path) {
+        line.push_str(&format!(" -> {}", link.target.display()));
+    }
+    if let Some(from) = plan.renamed.get(path) {
+        line.push_str(&format!(
+            "  {}",
+            dim.apply_to(format!("<- {}", from.display()))
+        ));
+    }
+    let file = plan.files.iter().find(|f| f.path == path);
+    if let Some(file) = file.filter(|f| !f.substitutions.is_empty()) {
+        line.push_str(&format!(
+            "  {}",
+            dim.apply_to(substitution_keys(&file.substitutions))
+        ));
+    }
+    line
+}
+
+/// `--verbose` summary of a finished scaffold of `plan`.
+pub fn verbose_summary(plan: &Plan, report: &ScaffoldReport) -> String {
+    format!(
+        "  {} directories, {} files ({} overwritten), {} links, {} placeholder substitutions",
+        plan.dirs.len(),
+        report.files.len(),
+        report.overwritten.len(),
+        plan.links.len() - report.skipped_links.len(),
+        report.substitutions
+    )
+}
+
+/// `--list --format json`: one object per template.
+pub fn templates_json(templates: &[&TemplateInfo]) -> Value {
+    templates
+        .iter()
+        .map(|t| {
+            json!({
+                "id": t.id,
+                "name": t.name(),
+                "description": t.manifest.description,
+                "tags": t.manifest.tags,
+            })
+        })
+        .collect()
+}
+
+/// `--format json` result of a scaffold that wrote `report` into `dest`.
+pub fn report_json(template: &str, dest: &Path, report: &ScaffoldReport, hooks_ok: bool) -> Value {
+    json!({
+        "template": template,
+        "destination": dest,
+        "files": report
+            .files
+            .iter()
+            .map(|f| json!({ "path": f.path, "substitutions": f.substitutions }))
+            .collect::<Vec<_>>(),
+        "substitutions": report.substitutions,
+        "overwritten": report.overwritten,
+        "skipped_links": report.skipped_links,
+        "unresolved": report.unresolved,
+        "setup_commands_succeeded": hooks_ok,
+    })
+}
+
+/// `--dry-run --format json`: what [`report_json`] would describe, unwritten,
+/// plus the setup commands that would run afterwards.
+pub fn plan_json(template: &str, plan: &Plan, setup_commands: &[String]) -> Value {
+    json!({
+        "template": template,
+        "destination": plan.dest,
+        "dry_run": true,
+        "directories": plan.dirs,
+        "files": plan
+            .files
+            .iter()
+            .map(|f| json!({ "path": f.path, "substitutions": f.substitutions }))
+            .collect::<Vec<_>>(),
+        "links": plan
+            .links
+            .iter()
+            .map(|l| json!({ "path": l.path, "target": l.target }))
+            .collect::<Vec<_>>(),
+        "substitutions": plan.substitution_count(),
+        "unresolved": plan.unresolved,
+        "setup_commands": setup_commands,
+    })
+}
+
+/// `--explain-source --format json`: how `--template` was classified and
+/// what it resolves to, or why it doesn't.
+pub fn explanation_json(explanation: &Explanation) -> Value {
+    json!({
+        "template": explanation.value,
+        "kind": explanation.kind,
+        "source": explanation.source,
+        "resolves_to": explanation.resolves_to,
+        "resolved": explanation.unresolved.is_none(),
+        "unresolved": explanation.unresolved,
+    })
+}
+
+/// `--print-config --format json`: each setting and placeholder value, as
+/// `(key, value, origin)`, keyed by name.
+pub fn config_json(
+    settings: &[(String, String, String)],
+    vars: &[(String, String, String)],
+) -> Value {
+    let entries = |entries: &[(String, String, String)]| -> serde_json::Map<String, Value> {
+        entries
+            .iter()
+            .map(|(key, value, origin)| (key.clone(), json!({ "value": value, "origin": origin })))
+            .collect()
+    };
+    json!({
+        "settings": entries(settings),
+        "vars": entries(vars),
+    })
+}
+
+/// `--format json` error object. The `kind` is a stable snake_case id to
+/// match on and the message is in English whatever `--lang` says. Scaffold
+/// failures, including those a [`CliError`] explains, also carry the path
+/// involved; errors that aren't the tool's own
+/// (such as I/O errors) have kind `"error"`.
+pub fn error_json(error: &(dyn std::error::Error + 'static)) -> Value {
+    let (kind, path): (&str, Option<&Path>) = match scaffold_error(error) {
+        Some(e) => match e {
+            ScaffoldError::DestinationExists(p) => ("destination_exists", Some(p)),
+            ScaffoldError::DestinationNotADirectory(p) => ("destination_not_a_directory", Some(p)),
+            ScaffoldError::InvalidProjectName { .. } => ("invalid_project_name", None),
+            ScaffoldError::TemplateNotFound(_) => ("template_not_found", None),
+            ScaffoldError::InvalidManifest { path, .. } => ("invalid_manifest", Some(path)),
+            ScaffoldError::InvalidIgnoreFile { path, .. } => ("invalid_ignore_file", Some(path)),
+            ScaffoldError::ReadFailed { path, .. } => ("read_failed", Some(path)),
+            ScaffoldError::CopyFailed { path, .. } => ("copy_failed", Some(path)),
+            ScaffoldError::PathCollision { target, .. } => ("path_collision", Some(target)),
+            ScaffoldError::InvalidFileName { path, .. } => ("invalid_file_name", Some(path)),
+            ScaffoldError::TooManySubstitutions { path, .. } => {
+                ("too_many_substitutions", Some(path))
+            }
+            ScaffoldError::PlaceholderRewrite { path, .. } => ("placeholder_rewrite", Some(path)),
+            ScaffoldError::UnresolvedPlaceholder { path, .. } => {
+                ("unresolved_placeholder", Some(path))
+            }
+        },
+        None => match error.downcast_ref::<CliError>() {
+            Some(e) => (e.kind, None),
+            None => ("error", None),
+        },
+    };
+    let message = match error.downcast_ref::<CliError>() {
+        Some(e) => e.message.clone(),
+        None => error.to_string(),
+    };
+
+    json!({
+        "error": {
+            "kind": kind,
+            "message": message,
+            "path": path,
+        }
+    })
+}
@@ -1,146 +1,2283 @@
-use std::fs;
-use std::io;
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use fs_extra::dir::{self, CopyOptions};
+use filetime::FileTime;
 use include_dir::Dir;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
 use walkdir::WalkDir;
 
-/// Copy the template directory into `output_dir/project_name` and replace every
-/// occurrence of the `{{project-name}}` placeholder in file contents with the
-/// real project name.
-pub fn scaffold(template_dir: &Path, output_dir: &Path, project_name: &str) -> io::Result<()> {
-    let dest = output_dir.join(project_name);
+use crate::manifest::{self, Manifest, TemplateInfo};
+use crate::project_manifest::{hash_file, hex, ProjectManifest};
+use crate::project_name::validate_project_name;
+use crate::secrets::SecretGenerator;
+use crate::vibeignore::IgnoreRules;
 
-    if dest.exists() {
-        return Err(io::Error::new(
-            io::ErrorKind::AlreadyExists,
-            format!("Destination already exists: {}", dest.display()),
-        ));
+/// Default cap on the number of placeholder substitutions in one scaffold.
+pub const DEFAULT_MAX_SUBSTITUTIONS: usize = 100_000;
+
+/// Initial version of a generated project, the built-in `version` placeholder.
+pub const DEFAULT_VERSION: &str = "0.1.0";
+
+/// Marker files whose only job is to keep an otherwise empty directory in a
+/// template, since git (and so a checked-out `templates/`) doesn't track
+/// empty directories. See [`ScaffoldOptions::strip_keep_files`].
+pub const KEEP_FILES: &[&str] = &[".gitkeep", ".keep"];
+
+/// What to scaffold and how: the project to create and how placeholders are
+/// substituted into it.
+#[derive(Debug, Clone)]
+pub struct ScaffoldOptions {
+    /// Name of the project folder, also the source of the built-in
+    /// placeholders (see [`build_placeholders`]).
+    pub project_name: String,
+    /// Directory the project folder is created in. It is created, along
+    /// with any missing parents, if it doesn't exist yet.
+    pub output_dir: PathBuf,
+    /// Values for `{{key}}` tokens, keyed without delimiters. They are applied
+    /// over the built-in placeholders, so they can add keys or override them.
+    pub placeholders: HashMap<String, String>,
+    /// Merge into an existing destination directory instead of failing.
+    pub force: bool,
+    /// Fail once more than this many substitutions would be made in total.
+    pub max_substitutions: usize,
+    /// Seed for `{{secret:N}}`/`{{password:N}}` values. `None` uses the OS
+    /// CSPRNG; a seed makes secrets reproducible and must only be used with
+    /// a loud warning.
+    pub secret_seed: Option<u64>,
+    /// Leave [`KEEP_FILES`] out of the project. The directories holding them
+    /// are still created, even when left empty.
+    pub strip_keep_files: bool,
+    /// Fail when a placeholder token is left unreplaced because nothing
+    /// supplies its key, instead of leaving it in the output.
+    pub strict: bool,
+    /// Enabled template features (see [`Feature`](crate::manifest::Feature)).
+    /// Entries under a `__if_<name>__` directory are skipped unless `name`
+    /// is listed here.
+    pub features: BTreeSet<String>,
+}
+
+impl ScaffoldOptions {
+    /// Options for creating `output_dir/project_name` with the built-in
+    /// placeholders only and default limits.
+    pub fn new(project_name: impl Into<String>, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            project_name: project_name.into(),
+            output_dir: output_dir.into(),
+            placeholders: HashMap::new(),
+            force: false,
+            max_substitutions: DEFAULT_MAX_SUBSTITUTIONS,
+            secret_seed: None,
+            strip_keep_files: false,
+            strict: false,
+            features: BTreeSet::new(),
+        }
     }
 
-    // --- copy the whole template tree ----------------------------------------
-    let mut opts = CopyOptions::new();
-    opts.copy_inside = true;
+    /// The built-in placeholders with [`placeholders`](Self::placeholders)
+    /// applied over them.
+    fn resolved_placeholders(&self) -> HashMap<String, String> {
+        let mut placeholders = build_placeholders(&self.project_name);
+        placeholders.extend(self.placeholders.clone());
+        placeholders
+    }
+}
 
-    dir::copy(template_dir, &dest, &opts).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            format!("Failed to copy template directory: {e}"),
-        )
-    })?;
+/// Why scaffolding failed. Every variant names the template or project path
+/// involved, so callers can report or act on the failure precisely.
+#[derive(Debug, Error)]
+pub enum ScaffoldError {
+    /// The destination exists and `force` was not set.
+    #[error("Destination already exists: {}", .0.display())]
+    DestinationExists(PathBuf),
+    /// The destination exists but is not a directory, so it can't be merged
+    /// into.
+    #[error("Destination exists and is not a directory: {}", .0.display())]
+    DestinationNotADirectory(PathBuf),
+    /// The project name can't be used as a directory name.
+    #[error("Invalid project name \"{name}\": {reason}")]
+    InvalidProjectName { name: String, reason: String },
+    /// No template with this name exists.
+    #[error("Template not found: {0}")]
+    TemplateNotFound(String),
+    /// A template's `template.toml` could not be parsed.
+    #[error("Invalid template manifest {}: {message}", path.display())]
+    InvalidManifest { path: PathBuf, message: String },
+    /// A template's `.vibeignore` has an invalid pattern.
+    #[error("Invalid ignore file {}: {message}", path.display())]
+    InvalidIgnoreFile { path: PathBuf, message: String },
+    /// Reading a template file or directory failed.
+    #[error("Failed to read {}: {source}", path.display())]
+    ReadFailed { path: PathBuf, source: io::Error },
+    /// Writing a file, directory or link into the project failed.
+    #[error("Failed to write {}: {source}", path.display())]
+    CopyFailed { path: PathBuf, source: io::Error },
+    /// Two template entries render to the same output path.
+    #[error(
+        "Both {} and {} would be written to {}",
+        first.display(),
+        second.display(),
+        target.display()
+    )]
+    PathCollision {
+        first: PathBuf,
+        second: PathBuf,
+        target: PathBuf,
+    },
+    /// A placeholder in a path expands to something that isn't a file name.
+    #[error(
+        "Placeholder in {} expands to an invalid file name \"{name}\"",
+        path.display()
+    )]
+    InvalidFileName { path: PathBuf, name: String },
+    /// More than `limit` substitutions would be made; `path` is the template
+    /// file being processed when the limit was hit.
+    #[error(
+        "Exceeded the limit of {limit} placeholder substitutions while processing {}",
+        path.display()
+    )]
+    TooManySubstitutions { limit: usize, path: PathBuf },
+    /// A placeholder in the template file at `path` was recognized but its
+    /// value could not be produced (e.g. `{{secret:3}}`).
+    #[error("{reason} in {}", path.display())]
+    PlaceholderRewrite { path: PathBuf, reason: String },
+    /// With `strict`, a placeholder token in the template entry at `path`
+    /// (in its contents or its name) has no value.
+    #[error("No value for placeholder {token} in {}", path.display())]
+    UnresolvedPlaceholder { path: PathBuf, token: String },
+}
+
+impl ScaffoldError {
+    /// Wrap an I/O error from reading the template at `path`.
+    fn read(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::ReadFailed {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Wrap an I/O error from writing `path` in the project.
+    fn copy(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::CopyFailed {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl From<walkdir::Error> for ScaffoldError {
+    fn from(e: walkdir::Error) -> Self {
+        Self::ReadFailed {
+            path: e.path().map(Path::to_path_buf).unwrap_or_default(),
+            source: e.into(),
+        }
+    }
+}
+
+/// What a successful scaffold did.
+#[derive(Debug)]
+pub struct ScaffoldReport {
+    /// Number of placeholder substitutions made.
+    pub substitutions: usize,
+    /// Every file written, relative to the project root, in write order.
+    pub files: Vec<WrittenFile>,
+    /// Files that already existed and were overwritten (only with `force`),
+    /// relative to the project root.
+    pub overwritten: Vec<PathBuf>,
+    /// Symbolic links that could not be recreated on this platform.
+    pub skipped_links: Vec<PathBuf>,
+    /// Placeholder tokens left unreplaced (see [`Plan::unresolved`]).
+    pub unresolved: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+/// A file written by a scaffold.
+#[derive(Debug)]
+pub struct WrittenFile {
+    /// Path relative to the project root.
+    pub path: PathBuf,
+    /// Number of placeholder substitutions made in its contents.
+    pub substitutions: usize,
+}
+
+/// Build the standard placeholders derived from the project name and the
+/// environment:
+///
+/// - `project-name`, and the name in other casings: `project-name-kebab`
+///   (`my-app`), `project-name-snake` (`my_app`), `project-name-pascal`
+///   (`MyApp`) and `project-name-screaming` (`MY_APP`),
+/// - `author`, from `git config user.name`, else `$USER`/`$USERNAME`, else
+///   empty,
+/// - `year`, the current year, or the year of `SOURCE_DATE_EPOCH` when it is
+///   set, so reproducible builds don't change at the new year,
+/// - `version`, [`DEFAULT_VERSION`].
+///
+/// The casings come out the same whichever style the name is written in:
+///
+/// ```
+/// use vibe_generate::scaffold::build_placeholders;
+///
+/// for name in ["my-cool-app", "MyCoolApp", "my_cool_app", "myCoolApp"] {
+///     let placeholders = build_placeholders(name);
+///     assert_eq!(placeholders["project-name"], name);
+///     assert_eq!(placeholders["project-name-kebab"], "my-cool-app");
+///     assert_eq!(placeholders["project-name-snake"], "my_cool_app");
+///     assert_eq!(placeholders["project-name-pascal"], "MyCoolApp");
+///     assert_eq!(placeholders["project-name-screaming"], "MY_COOL_APP");
+/// }
+/// ```
+pub fn build_placeholders(project_name: &str) -> HashMap<String, String> {
+    let words = split_words(project_name);
+
+    let kebab = words.join("-");
+    let snake = words.join("_");
+    let screaming = snake.to_uppercase();
+    let pascal: String = words
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    HashMap::from([
+        ("project-name".to_string(), project_name.to_string()),
+        ("project-name-kebab".to_string(), kebab),
+        ("project-name-snake".to_string(), snake),
+        ("project-name-pascal".to_string(), pascal),
+        ("project-name-screaming".to_string(), screaming),
+        ("author".to_string(), detect_author()),
+        ("year".to_string(), current_year().to_string()),
+        ("version".to_string(), DEFAULT_VERSION.to_string()),
+    ])
+}
 
-    // --- replace placeholders in every file ----------------------------------
-    replace_placeholders(&dest, project_name)?;
+/// Split a name into lowercase words on `-`, `_`, whitespace and camel-case
+/// boundaries, so `my-app`, `my_app` and `MyApp` all give `["my", "app"]`.
+/// A run of capitals is kept together as an acronym (`HTTPServer` gives
+/// `["http", "server"]`).
+fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '-' || c == '_' || c.is_whitespace() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+/// The user's name from git, falling back to the login name. Never fails:
+/// an undeterminable author is an empty string.
+fn detect_author() -> String {
+    let from_git = std::process::Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    from_git
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_default()
+}
+
+/// The current (UTC) calendar year, or that of `SOURCE_DATE_EPOCH` when it is
+/// set to a valid timestamp. An invalid one is reported by
+/// [`reproducible_mtime`].
+fn current_year() -> i64 {
+    let source_date_epoch = std::env::var("SOURCE_DATE_EPOCH").ok();
+    year_at(source_date_epoch.as_deref(), SystemTime::now())
+}
+
+/// The year of `source_date_epoch` if it is a valid timestamp, else of `now`.
+fn year_at(source_date_epoch: Option<&str>, now: SystemTime) -> i64 {
+    let secs = source_date_epoch
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|secs| *secs >= 0)
+        .unwrap_or_else(|| {
+            now.duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64)
+        });
+    civil_from_days(secs.div_euclid(86_400)).0
+}
 
+/// Format `time` as an RFC 3339 UTC timestamp, to the second.
+pub(crate) fn rfc3339(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let rem = secs.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// The `(year, month, day)` of a count of days since the Unix epoch
+/// (Howard Hinnant's civil-from-days algorithm).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Template files larger than this many bytes are not loaded into memory:
+/// they are substituted in chunks while being copied.
+pub const STREAM_THRESHOLD: u64 = 1024 * 1024;
+
+/// Size of the chunks streamed files are read in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Longest placeholder key that is looked up. It bounds how far ahead a
+/// streamed file is buffered to decide whether a delimiter starts a token.
+const MAX_KEY_LEN: usize = 256;
+
+/// A file the scaffold will create.
+pub struct PlannedFile {
+    /// Path relative to the project root, with placeholders in its name
+    /// replaced.
+    pub path: PathBuf,
+    /// Contents after placeholder substitution, or where to stream them from.
+    pub contents: FileContents,
+    /// How many times each placeholder key is substituted in the contents.
+    pub substitutions: BTreeMap<String, usize>,
+    /// Whether to mark the file executable (Unix only).
+    pub executable: bool,
+}
+
+/// The contents of a [`PlannedFile`].
+pub enum FileContents {
+    /// The substituted contents, held in memory.
+    Bytes(Vec<u8>),
+    /// A template file larger than [`STREAM_THRESHOLD`], substituted in chunks
+    /// while it is copied rather than loaded whole.
+    Streamed(StreamedFile),
+}
+
+/// A large template file that is substituted while it is copied.
+pub struct StreamedFile {
+    /// The file in the template.
+    pub source: PathBuf,
+    /// The template's placeholder delimiters.
+    delimiters: (String, String),
+    /// Values of the placeholders found in it while planning, so the copy
+    /// uses the same (possibly generated) values the plan counted.
+    values: HashMap<String, String>,
+}
+
+/// A symbolic link the scaffold will recreate.
+pub struct PlannedLink {
+    /// Path of the link relative to the project root, with placeholders in
+    /// its name replaced.
+    pub path: PathBuf,
+    /// The link target, exactly as in the template (never substituted).
+    pub target: PathBuf,
+}
+
+/// Everything a scaffold will write, computed without touching the
+/// destination. Built by [`plan`] or [`plan_embedded`], then either printed
+/// (for `--dry-run`) or carried out with [`Plan::execute`].
+pub struct Plan {
+    /// The project directory that will be created.
+    pub dest: PathBuf,
+    /// Directories to create, relative to `dest`, parents before children.
+    pub dirs: Vec<PathBuf>,
+    /// Files to write, relative to `dest`.
+    pub files: Vec<PlannedFile>,
+    /// Symbolic links to recreate, relative to `dest`.
+    pub links: Vec<PlannedLink>,
+    /// For each directory, file and link whose name contained placeholders,
+    /// its path in the template, keyed by its rendered path.
+    pub renamed: HashMap<PathBuf, PathBuf>,
+    /// Placeholder tokens (with their delimiters) that nothing supplies a
+    /// value for, so they are left as they are, by the rendered path of the
+    /// entry whose name or contents hold them.
+    pub unresolved: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+impl Plan {
+    /// Total number of placeholder substitutions across all files.
+    pub fn substitution_count(&self) -> usize {
+        self.files
+            .iter()
+            .flat_map(|f| f.substitutions.values())
+            .sum()
+    }
+
+    /// Create the destination and write every planned directory, file and
+    /// link, reporting each written file to `progress`.
+    ///
+    /// A new destination is built in a hidden staging directory beside it
+    /// and renamed into place once everything has been written, so it never
+    /// exists half-built. If writing fails, the staging directory and any
+    /// parent directories this call created are removed before the error is
+    /// returned.
+    ///
+    /// An existing destination (with `force`) is written into in place:
+    /// files and links already present at a planned path are overwritten,
+    /// and anything else is left alone. Links are only created on Unix;
+    /// elsewhere they are skipped and listed in the report.
+    pub fn execute(&self, progress: ProgressFn) -> Result<ScaffoldReport, ScaffoldError> {
+        if self.dest.symlink_metadata().is_ok() {
+            return self.write_into(&self.dest, progress);
+        }
+
+        let parent = match self.dest.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let mut rollback = Rollback::new(parent);
+        fs::create_dir_all(parent).map_err(ScaffoldError::copy(parent))?;
+        let staging = staging_dir(&self.dest, parent)?;
+
+        let report = self
+            .write_into(staging.path(), progress)
+            .map_err(|e| unstaged(e, staging.path(), &self.dest))?;
+        move_into_place(staging, &self.dest)?;
+        rollback.disarm();
+        Ok(report)
+    }
+
+    /// Write every planned directory, file and link under `root`.
+    fn write_into(
+        &self,
+        root: &Path,
+        progress: ProgressFn,
+    ) -> Result<ScaffoldReport, ScaffoldError> {
+        for dir in &self.dirs {
+            let out_path = root.join(dir);
+            fs::create_dir_all(&out_path).map_err(ScaffoldError::copy(&out_path))?;
+        }
+
+        // Files are written in parallel. Workers report each finished file
+        // over a channel so `progress` still runs on this thread only.
+        let total = self.files.len();
+        let (done_tx, done_rx) = mpsc::channel();
+        let written = thread::scope(|scope| {
+            let writer = scope.spawn(move || {
+                self.files
+                    .par_iter()
+                    .map_with(done_tx, |done_tx, file| {
+                        let written = write_file(root, file);
+                        let _ = done_tx.send(&file.path);
+                        written
+                    })
+                    .collect::<Vec<_>>()
+            });
+            for (i, path) in done_rx.iter().enumerate() {
+                progress(i + 1, total, path);
+            }
+            writer.join().expect("file writer thread panicked")
+        });
+
+        // The first failure in plan order is reported, whichever worker hit
+        // it first.
+        let mut overwritten = Vec::new();
+        for (file, existed) in self.files.iter().zip(written) {
+            if existed? {
+                overwritten.push(file.path.clone());
+            }
+        }
+
+        let mut skipped_links = Vec::new();
+        for link in &self.links {
+            if !cfg!(unix) {
+                skipped_links.push(link.path.clone());
+                continue;
+            }
+            let out_path = root.join(&link.path);
+            if out_path.symlink_metadata().is_ok() {
+                overwritten.push(link.path.clone());
+                fs::remove_file(&out_path).map_err(ScaffoldError::copy(&out_path))?;
+            }
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&link.target, &out_path)
+                .map_err(ScaffoldError::copy(&out_path))?;
+        }
+
+        Ok(ScaffoldReport {
+            substitutions: self.substitution_count(),
+            files: self
+                .files
+                .iter()
+                .map(|file| WrittenFile {
+                    path: file.path.clone(),
+                    substitutions: file.substitutions.values().sum(),
+                })
+                .collect(),
+            overwritten,
+            skipped_links,
+            unresolved: self.unresolved.clone(),
+        })
+    }
+}
+
+/// Write one planned file under `root`, returning whether something already
+/// existed at its path.
+fn write_file(root: &Path, file: &PlannedFile) -> Result<bool, ScaffoldError> {
+    let out_path = root.join(&file.path);
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent).map_err(ScaffoldError::copy(parent))?;
+    }
+    let existed = out_path.symlink_metadata().is_ok();
+    match &file.contents {
+        FileContents::Bytes(contents) => {
+            if existed && out_path.is_symlink() {
+                // Replace the link itself rather than writing through it.
+                fs::remove_file(&out_path).map_err(ScaffoldError::copy(&out_path))?;
+            }
+            fs::write(&out_path, contents).map_err(ScaffoldError::copy(&out_path))?;
+        }
+        FileContents::Streamed(streamed) => stream_file(streamed, &out_path)?,
+    }
+    #[cfg(unix)]
+    if file.executable {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&out_path, fs::Permissions::from_mode(0o755))
+            .map_err(ScaffoldError::copy(&out_path))?;
+    }
+    Ok(existed)
+}
+
+/// Create the staging directory for `dest` in `parent`: hidden, named after
+/// the project (`.my-app.a1B2c3.tmp`), and on the same filesystem so it can
+/// be renamed into place. It is removed when dropped.
+fn staging_dir(dest: &Path, parent: &Path) -> Result<tempfile::TempDir, ScaffoldError> {
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    let prefix = format!(".{name}.");
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix).suffix(".tmp");
+    // Created like `fs::create_dir` would create the project: 0o777 less
+    // the umask, not the owner-only default of temporary directories.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o777));
+    }
+    builder
+        .tempdir_in(parent)
+        .map_err(ScaffoldError::copy(parent))
+}
+
+/// Name the path in `dest` rather than in the `staging` directory (which is
+/// removed) in a write error.
+fn unstaged(error: ScaffoldError, staging: &Path, dest: &Path) -> ScaffoldError {
+    match error {
+        ScaffoldError::CopyFailed { path, source } => {
+            let path = match path.strip_prefix(staging) {
+                Ok(rel) => dest.join(rel),
+                Err(_) => path,
+            };
+            ScaffoldError::CopyFailed { path, source }
+        }
+        other => other,
+    }
+}
+
+/// Rename the finished `staging` directory to `dest`. Where that isn't
+/// possible across filesystems, its contents are copied instead; a partial
+/// copy is removed again.
+fn move_into_place(staging: tempfile::TempDir, dest: &Path) -> Result<(), ScaffoldError> {
+    // Something may have appeared there since planning; an empty directory
+    // would otherwise be silently replaced.
+    if dest.symlink_metadata().is_ok() {
+        return Err(ScaffoldError::DestinationExists(dest.to_path_buf()));
+    }
+    match fs::rename(staging.path(), dest) {
+        Ok(()) => {
+            // Nothing is left at the staging path to clean up.
+            let _ = staging.keep();
+            Ok(())
+        }
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => copy_tree(staging.path(), dest)
+            .map_err(|e| {
+                let _ = fs::remove_dir_all(dest);
+                ScaffoldError::copy(dest)(e)
+            }),
+        Err(e) => Err(ScaffoldError::copy(dest)(e)),
+    }
+}
+
+/// Recursively copy the directory `from` to a new directory `to`, keeping
+/// file permissions and recreating symbolic links.
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        let out_path = to.join(rel);
+        let kind = entry.file_type();
+        if kind.is_dir() {
+            fs::create_dir(&out_path)?;
+        } else if kind.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(fs::read_link(entry.path())?, &out_path)?;
+        } else {
+            fs::copy(entry.path(), &out_path)?;
+        }
+    }
     Ok(())
 }
 
-/// Scaffold from embedded (compile-time) templates.
+/// Copy a streamed file to `out_path`, substituting as it goes. The copy is
+/// written to a temporary file beside `out_path` and renamed over it once
+/// complete, so `out_path` never holds a partial file (a link there is
+/// replaced, not written through).
+fn stream_file(file: &StreamedFile, out_path: &Path) -> Result<(), ScaffoldError> {
+    let dir = out_path.parent().unwrap_or(Path::new("."));
+    let mut builder = tempfile::Builder::new();
+    // Created like `fs::write` would create it: 0o666 less the umask.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o666));
+    }
+    let mut temp = builder
+        .tempfile_in(dir)
+        .map_err(ScaffoldError::copy(out_path))?;
+
+    let mut writer = BufWriter::new(temp.as_file_mut());
+    render_streamed(file, &mut writer, out_path)?;
+    writer.flush().map_err(ScaffoldError::copy(out_path))?;
+    drop(writer);
+
+    temp.persist(out_path)
+        .map_err(|e| ScaffoldError::copy(out_path)(e.error))?;
+    Ok(())
+}
+
+/// Substitute a streamed file into `writer`; `out_path` names write errors.
+fn render_streamed(
+    file: &StreamedFile,
+    writer: &mut dyn Write,
+    out_path: &Path,
+) -> Result<(), ScaffoldError> {
+    let mut reader = File::open(&file.source).map_err(ScaffoldError::read(&file.source))?;
+    let mut lookup = |key: &str| file.values.get(key).cloned().map(Ok);
+    let delimiters = (file.delimiters.0.as_str(), file.delimiters.1.as_str());
+    match substitute_stream(&mut reader, writer, delimiters, &mut lookup, usize::MAX) {
+        Ok(_) => Ok(()),
+        Err(SubstituteError::Read(source)) => Err(ScaffoldError::ReadFailed {
+            path: file.source.clone(),
+            source,
+        }),
+        Err(SubstituteError::Write(source)) => Err(ScaffoldError::CopyFailed {
+            path: out_path.to_path_buf(),
+            source,
+        }),
+        // Every value was resolved when planning.
+        Err(SubstituteError::LimitExceeded | SubstituteError::Invalid(_)) => unreachable!(),
+    }
+}
+
+/// A template re-applied over an existing project by [`plan_update`]: what
+/// happens to each of its files, and the [`Plan`] that carries it out.
+pub struct UpdatePlan {
+    /// Writes the added and updated files, and any missing directories and
+    /// links. Everything else is left out.
+    pub plan: Plan,
+    /// Files the project doesn't have yet.
+    pub added: Vec<PathBuf>,
+    /// Files the user hasn't changed since they were generated, whose
+    /// template version has changed since.
+    pub updated: Vec<PathBuf>,
+    /// Files that already match the template.
+    pub unchanged: Vec<PathBuf>,
+    /// Files that differ from the template and were changed since they were
+    /// generated (or weren't generated at all). They are not touched.
+    pub conflicts: Vec<PathBuf>,
+    /// Unchanged files with generated secrets in them. They are never
+    /// rewritten, as their secrets would change.
+    pub kept_secrets: Vec<PathBuf>,
+}
+
+/// Narrow `plan`, made with `force` for an existing project, to the files
+/// that can safely be written. `recorded` is the project's
+/// [`ProjectManifest`]; without one, every existing file that differs from
+/// the template is a conflict.
+pub fn plan_update(
+    plan: Plan,
+    recorded: Option<&ProjectManifest>,
+) -> Result<UpdatePlan, ScaffoldError> {
+    let mut update = UpdatePlan {
+        plan: Plan {
+            dest: plan.dest,
+            dirs: plan.dirs,
+            files: Vec::new(),
+            links: Vec::new(),
+            renamed: plan.renamed,
+            unresolved: plan.unresolved,
+        },
+        added: Vec::new(),
+        updated: Vec::new(),
+        unchanged: Vec::new(),
+        conflicts: Vec::new(),
+        kept_secrets: Vec::new(),
+    };
+
+    for file in plan.files {
+        let out_path = update.plan.dest.join(&file.path);
+        if out_path.symlink_metadata().is_err() {
+            update.added.push(file.path.clone());
+            update.plan.files.push(file);
+            continue;
+        }
+
+        let current = hash_file(&out_path).map_err(ScaffoldError::read(&out_path))?;
+        if current == content_hash(&file, &out_path)? {
+            update.unchanged.push(file.path);
+        } else if recorded.and_then(|r| r.hash(&file.path)) != Some(current.as_str()) {
+            update.conflicts.push(file.path);
+        } else if file
+            .substitutions
+            .keys()
+            .any(|key| key.starts_with("secret:") || key.starts_with("password:"))
+        {
+            update.kept_secrets.push(file.path);
+        } else {
+            update.updated.push(file.path.clone());
+            update.plan.files.push(file);
+        }
+    }
+
+    for link in plan.links {
+        if update
+            .plan
+            .dest
+            .join(&link.path)
+            .symlink_metadata()
+            .is_err()
+        {
+            update.added.push(link.path.clone());
+            update.plan.links.push(link);
+        }
+    }
+
+    Ok(update)
+}
+
+/// Hex SHA-256 of a planned file's contents; `out_path` names write errors.
+fn content_hash(file: &PlannedFile, out_path: &Path) -> Result<String, ScaffoldError> {
+    let mut hasher = Sha256::new();
+    match &file.contents {
+        FileContents::Bytes(contents) => hasher.update(contents),
+        FileContents::Streamed(streamed) => render_streamed(streamed, &mut hasher, out_path)?,
+    }
+    Ok(hex(hasher))
+}
+
+/// Removes the missing parents of a new destination when dropped, unless
+/// disarmed after the scaffold succeeds, so a failure partway through leaves
+/// no empty output directories behind.
+struct Rollback {
+    /// The outermost directory that didn't exist yet, if any.
+    created: Option<PathBuf>,
+}
+
+impl Rollback {
+    /// Guard the creation of `dir`, remembering its outermost missing
+    /// ancestor (or `dir` itself) as the tree to remove on failure.
+    fn new(dir: &Path) -> Self {
+        let created = dir
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && dir.symlink_metadata().is_err())
+            .last()
+            .map(Path::to_path_buf);
+        Self { created }
+    }
+
+    fn disarm(&mut self) {
+        self.created = None;
+    }
+}
+
+impl Drop for Rollback {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.created {
+            // Best effort: the original error is what gets reported.
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// Callback reporting write progress as `(files_done, files_total,
+/// current_path)`, where `current_path` is relative to the project root.
+///
+/// It is invoked synchronously on the thread that called the scaffold
+/// function, once after each file is written, so it needs no `Send` bound.
+pub type ProgressFn<'a> = &'a mut dyn FnMut(usize, usize, &Path);
+
+/// Copy the template directory into `options.output_dir/options.project_name`
+/// and replace every `{{key}}` placeholder in file contents and names,
+/// reporting each written file to `progress`.
+///
+/// An existing destination is an error unless `options.force` is set, in
+/// which case the template is merged into it (see [`Plan::execute`]).
+pub fn scaffold(
+    template_dir: &Path,
+    options: &ScaffoldOptions,
+    progress: ProgressFn,
+) -> Result<ScaffoldReport, ScaffoldError> {
+    plan(template_dir, options)?.execute(progress)
+}
+
+/// Scaffold from embedded (compile-time) templates, like [`scaffold`].
 pub fn scaffold_embedded(
     embedded: &Dir,
     template_name: &str,
-    output_dir: &Path,
-    project_name: &str,
-) -> io::Result<()> {
-    let dest = output_dir.join(project_name);
-
-    if dest.exists() {
-        return Err(io::Error::new(
-            io::ErrorKind::AlreadyExists,
-            format!("Destination already exists: {}", dest.display()),
+    options: &ScaffoldOptions,
+    progress: ProgressFn,
+) -> Result<ScaffoldReport, ScaffoldError> {
+    plan_embedded(embedded, template_name, options)?.execute(progress)
+}
+
+/// A template entry: its path relative to the template root and what it is.
+type TemplateEntry = (PathBuf, EntryKind);
+
+/// What a template entry is and, for files, what it holds.
+enum EntryKind {
+    Dir,
+    /// A file with its raw contents (or, for a large one, its path) and
+    /// whether its source is executable.
+    File {
+        contents: FileContents,
+        executable: bool,
+    },
+    /// A symbolic link and its target.
+    Symlink(PathBuf),
+}
+
+/// Plan scaffolding from a filesystem template without writing anything.
+///
+/// Fails if the destination already exists and `options.force` is not set,
+/// so a dry run reports the same error a real run would.
+pub fn plan(template_dir: &Path, options: &ScaffoldOptions) -> Result<Plan, ScaffoldError> {
+    let dest = check_destination(options)?;
+    if !template_dir.is_dir() {
+        return Err(ScaffoldError::TemplateNotFound(
+            template_dir.display().to_string(),
         ));
     }
 
-    let template_dir = embedded.get_dir(template_name).ok_or_else(|| {
-        io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("Embedded template not found: {template_name}"),
-        )
-    })?;
+    let ignore = IgnoreRules::load(template_dir)?;
 
-    // Extract embedded files to disk.
-    extract_dir(template_dir, &dest)?;
+    // Symlinks inside the template are recreated as links, not followed.
+    // Ignored directories are pruned rather than walked.
+    let mut entries: Vec<TemplateEntry> = Vec::new();
+    let mut to_read = Vec::new();
+    let walker = WalkDir::new(template_dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.path().strip_prefix(template_dir).map_or(true, |rel| {
+                !ignore.is_ignored(rel, entry.file_type().is_dir())
+            })
+        });
+    for entry in walker {
+        let entry = entry?;
+        let path = entry.path();
+        let rel = path
+            .strip_prefix(template_dir)
+            .expect("walkdir yields paths under its root")
+            .to_path_buf();
+        if entry.file_type().is_symlink() {
+            let target = fs::read_link(path).map_err(ScaffoldError::read(path))?;
+            entries.push((rel, EntryKind::Symlink(target)));
+        } else if entry.file_type().is_dir() {
+            entries.push((rel, EntryKind::Dir));
+        } else {
+            let metadata = entry.metadata()?;
+            let contents = if metadata.len() > STREAM_THRESHOLD {
+                FileContents::Streamed(StreamedFile {
+                    source: path.to_path_buf(),
+                    delimiters: Default::default(),
+                    values: HashMap::new(),
+                })
+            } else {
+                to_read.push((entries.len(), path.to_path_buf()));
+                FileContents::Bytes(Vec::new())
+            };
+            entries.push((
+                rel,
+                EntryKind::File {
+                    contents,
+                    executable: is_executable(&metadata),
+                },
+            ));
+        }
+    }
 
-    // Replace placeholders.
-    replace_placeholders(&dest, project_name)?;
+    // Read file contents in parallel, reporting the first failure in walk
+    // order.
+    let read: Vec<Result<Vec<u8>, ScaffoldError>> = to_read
+        .par_iter()
+        .map(|(_, path)| fs::read(path).map_err(ScaffoldError::read(path)))
+        .collect();
+    for ((index, _), read) in to_read.iter().zip(read) {
+        if let EntryKind::File { contents, .. } = &mut entries[*index].1 {
+            *contents = FileContents::Bytes(read?);
+        }
+    }
 
-    Ok(())
+    let manifest = manifest::load(template_dir)?;
+    build_plan(dest, entries, &manifest, options)
 }
 
-/// Recursively extract an embedded directory to disk.
-fn extract_dir(dir: &Dir, dest: &Path) -> io::Result<()> {
-    fs::create_dir_all(dest)?;
+/// Plan scaffolding from an embedded template without writing anything.
+pub fn plan_embedded(
+    embedded: &Dir,
+    template_name: &str,
+    options: &ScaffoldOptions,
+) -> Result<Plan, ScaffoldError> {
+    let dest = check_destination(options)?;
+
+    let template_dir = embedded
+        .get_dir(template_name)
+        .ok_or_else(|| ScaffoldError::TemplateNotFound(template_name.to_string()))?;
 
+    let mut entries = Vec::new();
+    collect_embedded(template_dir, Path::new(template_name), &mut entries);
+    let ignore = IgnoreRules::load_embedded(embedded, template_name)?;
+    entries.retain(|(rel, kind)| !ignore.is_ignored(rel, matches!(kind, EntryKind::Dir)));
+
+    let manifest = manifest::load_embedded(embedded, template_name)?;
+    build_plan(dest, entries, &manifest, options)
+}
+
+/// Whether a template file has any executable bit set. Always `false` off
+/// Unix, where there are no mode bits to carry over.
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        false
+    }
+}
+
+/// Return the destination directory, failing if the project name is invalid
+/// or the directory already exists, unless `force` is set and it is a
+/// directory that can be merged into.
+fn check_destination(options: &ScaffoldOptions) -> Result<PathBuf, ScaffoldError> {
+    validate_project_name(&options.project_name)?;
+    let dest = options.output_dir.join(&options.project_name);
+
+    if options.force && dest.exists() && !dest.is_dir() {
+        return Err(ScaffoldError::DestinationNotADirectory(dest));
+    }
+    if !options.force && dest.exists() {
+        return Err(ScaffoldError::DestinationExists(dest));
+    }
+
+    Ok(dest)
+}
+
+/// Recursively collect an embedded directory's entries, relative to `root`.
+fn collect_embedded(dir: &Dir, root: &Path, entries: &mut Vec<TemplateEntry>) {
     for file in dir.files() {
-        let file_name = file
-            .path()
-            .file_name()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid embedded file path"))?;
-        let out_path = dest.join(file_name);
-        fs::write(&out_path, file.contents())?;
+        let rel = file.path().strip_prefix(root).unwrap_or(file.path());
+        entries.push((
+            rel.to_path_buf(),
+            EntryKind::File {
+                contents: FileContents::Bytes(file.contents().to_vec()),
+                executable: false,
+            },
+        ));
     }
 
     for subdir in dir.dirs() {
-        let dir_name = subdir
-            .path()
+        let rel = subdir.path().strip_prefix(root).unwrap_or(subdir.path());
+        entries.push((rel.to_path_buf(), EntryKind::Dir));
+        collect_embedded(subdir, root, entries);
+    }
+}
+
+/// Render template entries into a [`Plan`]: substitute placeholders in every
+/// path and in the contents of every file, whatever its encoding.
+///
+/// Fails if more than `max_substitutions` content substitutions would be made
+/// in total, as a guard against templates crafted to blow up the output, or if
+/// two entries would be written to the same path.
+///
+/// Contents are substituted in parallel, but errors are still reported for
+/// the first offending file in template order.
+fn build_plan(
+    dest: PathBuf,
+    entries: Vec<TemplateEntry>,
+    manifest: &Manifest,
+    options: &ScaffoldOptions,
+) -> Result<Plan, ScaffoldError> {
+    let placeholders = options.resolved_placeholders();
+    let delimiters = manifest.delimiters();
+    let max = options.max_substitutions;
+    let secrets = Mutex::new(SecretGenerator::new(options.secret_seed));
+
+    let mut plan = Plan {
+        dest,
+        dirs: Vec::new(),
+        files: Vec::new(),
+        links: Vec::new(),
+        renamed: HashMap::new(),
+        unresolved: BTreeMap::new(),
+    };
+    let mut targets: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut files = Vec::new();
+
+    for (rel, kind) in entries {
+        if options.strip_keep_files && is_keep_file(&rel, &kind) {
+            continue;
+        }
+        let Some(included) = feature_path(&rel, &options.features) else {
+            continue;
+        };
+        let mut unresolved = BTreeSet::new();
+        let path = render_path(&included, &placeholders, delimiters, &mut unresolved)?;
+        if let Some(token) = unresolved.first().filter(|_| options.strict) {
+            return Err(ScaffoldError::UnresolvedPlaceholder {
+                path: rel,
+                token: token.clone(),
+            });
+        }
+        if !unresolved.is_empty() {
+            plan.unresolved.insert(path.clone(), unresolved);
+        }
+        if let Some(first) = targets.get(&path) {
+            // A conditional directory may coincide with an unconditional
+            // one (`__if_ci__/.github` and `.github`); their contents merge.
+            let both_dirs = matches!(kind, EntryKind::Dir) && plan.dirs.contains(&path);
+            if both_dirs {
+                continue;
+            }
+            return Err(ScaffoldError::PathCollision {
+                first: first.clone(),
+                second: rel,
+                target: path,
+            });
+        }
+        targets.insert(path.clone(), rel.clone());
+        if path != rel {
+            plan.renamed.insert(path.clone(), rel.clone());
+        }
+
+        match kind {
+            EntryKind::Dir => plan.dirs.push(path),
+            EntryKind::Symlink(target) => plan.links.push(PlannedLink { path, target }),
+            EntryKind::File {
+                contents,
+                executable,
+            } => {
+                let executable = executable || manifest.is_executable(&rel);
+                files.push((rel, path, contents, executable));
+            }
+        }
+    }
+
+    // Substitution works on raw bytes, so files in other encodings (or with
+    // stray invalid bytes) are handled too; files without a match are kept
+    // byte-for-byte. Streamed files are only scanned here, to count their
+    // placeholders and fix their values. Each file may use the whole budget
+    // here; the running total is checked below, in order.
+    let rendered: Vec<_> = files
+        .par_iter()
+        .map(|(_, _, contents, _)| {
+            let mut counts = BTreeMap::new();
+            let mut values = HashMap::new();
+            let mut unresolved = BTreeSet::new();
+            let mut lookup = |key: &str| {
+                let value = match placeholders.get(key) {
+                    Some(value) => Some(Ok(value.clone())),
+                    None => secrets
+                        .lock()
+                        .expect("secret generator lock poisoned")
+                        .resolve(key),
+                };
+                let Some(value) = value else {
+                    if is_placeholder_key(key) {
+                        unresolved.insert(format!("{}{key}{}", delimiters.0, delimiters.1));
+                    }
+                    return None;
+                };
+                *counts.entry(key.to_string()).or_insert(0) += 1;
+                if let Ok(value) = &value {
+                    values.insert(key.to_string(), value.clone());
+                }
+                Some(value)
+            };
+            let result = match contents {
+                FileContents::Bytes(bytes) => substitute(bytes, delimiters, &mut lookup, max),
+                FileContents::Streamed(streamed) => File::open(&streamed.source)
+                    .map_err(SubstituteError::Read)
+                    .and_then(|mut reader| {
+                        let mut sink = io::sink();
+                        substitute_stream(&mut reader, &mut sink, delimiters, &mut lookup, max)
+                    })
+                    .map(|count| (count > 0).then_some((Vec::new(), count))),
+            };
+            result.map(|replaced| (replaced, counts, values, unresolved))
+        })
+        .collect();
+
+    let mut total = 0;
+    for ((rel, path, contents, executable), rendered) in files.into_iter().zip(rendered) {
+        let (replaced, counts, values, unresolved) = match rendered {
+            Ok(rendered) => rendered,
+            Err(SubstituteError::LimitExceeded) => {
+                return Err(ScaffoldError::TooManySubstitutions {
+                    limit: max,
+                    path: rel,
+                });
+            }
+            Err(SubstituteError::Invalid(message)) => {
+                return Err(ScaffoldError::PlaceholderRewrite {
+                    path: rel,
+                    reason: message,
+                });
+            }
+            Err(SubstituteError::Read(source) | SubstituteError::Write(source)) => {
+                let path = match contents {
+                    FileContents::Streamed(streamed) => streamed.source,
+                    FileContents::Bytes(_) => rel,
+                };
+                return Err(ScaffoldError::ReadFailed { path, source });
+            }
+        };
+
+        if let Some(token) = unresolved.first().filter(|_| options.strict) {
+            return Err(ScaffoldError::UnresolvedPlaceholder {
+                path: rel,
+                token: token.clone(),
+            });
+        }
+        if !unresolved.is_empty() {
+            plan.unresolved
+                .entry(path.clone())
+                .or_default()
+                .extend(unresolved);
+        }
+
+        let count = replaced.as_ref().map_or(0, |(_, count)| *count);
+        total += count;
+        if total > max {
+            return Err(ScaffoldError::TooManySubstitutions {
+                limit: max,
+                path: rel,
+            });
+        }
+
+        let contents = match (contents, replaced) {
+            (FileContents::Bytes(_), Some((replaced, _))) => FileContents::Bytes(replaced),
+            (FileContents::Streamed(mut streamed), _) => {
+                streamed.delimiters = (delimiters.0.to_string(), delimiters.1.to_string());
+                streamed.values = values;
+                FileContents::Streamed(streamed)
+            }
+            (contents, None) => contents,
+        };
+
+        plan.files.push(PlannedFile {
+            path,
+            contents,
+            substitutions: counts,
+            executable,
+        });
+    }
+
+    Ok(plan)
+}
+
+/// Whether a template entry is one of the [`KEEP_FILES`].
+fn is_keep_file(rel: &Path, kind: &EntryKind) -> bool {
+    matches!(kind, EntryKind::File { .. })
+        && rel
             .file_name()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid embedded dir path"))?;
-        extract_dir(subdir, &dest.join(dir_name))?;
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| KEEP_FILES.contains(&name))
+}
+
+/// The path a template entry is generated at, given the enabled `features`:
+/// `rel` without its `__if_<feature>__` components, or `None` when one of
+/// those features is off or the entry is a marker directory itself.
+fn feature_path(rel: &Path, features: &BTreeSet<String>) -> Option<PathBuf> {
+    if rel
+        .components()
+        .next_back()
+        .and_then(feature_marker)
+        .is_some()
+    {
+        return None;
+    }
+
+    let mut path = PathBuf::new();
+    for component in rel.components() {
+        match feature_marker(component) {
+            Some(name) if !features.contains(name) => return None,
+            Some(_) => {}
+            None => path.push(component),
+        }
+    }
+    Some(path)
+}
+
+/// The feature a `__if_<feature>__` path component depends on.
+fn feature_marker(component: Component<'_>) -> Option<&str> {
+    let name = component.as_os_str().to_str()?;
+    name.strip_prefix("__if_")?.strip_suffix("__")
+}
+
+/// Substitute placeholders in every component of a relative template path,
+/// adding the tokens in its last name that nothing supplies a value for to
+/// `unresolved`.
+///
+/// Fails if a value would put a path separator, `.` or `..` into a name.
+fn render_path(
+    rel: &Path,
+    placeholders: &HashMap<String, String>,
+    delimiters: (&str, &str),
+    unresolved: &mut BTreeSet<String>,
+) -> Result<PathBuf, ScaffoldError> {
+    let mut rendered = PathBuf::new();
+    let count = rel.components().count();
+
+    for (i, component) in rel.components().enumerate() {
+        // Parent names are rendered, and so reported, with their own entries.
+        let last = i + 1 == count;
+        let mut lookup = |key: &str| {
+            let value = placeholders.get(key).cloned().map(Ok);
+            if value.is_none() && last && is_placeholder_key(key) {
+                unresolved.insert(format!("{}{key}{}", delimiters.0, delimiters.1));
+            }
+            value
+        };
+        let name = component.as_os_str();
+        let Some(new_name) = name
+            .to_str()
+            .and_then(|n| {
+                substitute(n.as_bytes(), delimiters, &mut lookup, usize::MAX)
+                    .ok()
+                    .flatten()
+            })
+            // Names and values are both UTF-8, so the result is too.
+            .and_then(|(n, _)| String::from_utf8(n).ok())
+        else {
+            rendered.push(name);
+            continue;
+        };
+
+        if new_name.is_empty()
+            || new_name == "."
+            || new_name == ".."
+            || new_name.contains(['/', '\\'])
+        {
+            return Err(ScaffoldError::InvalidFileName {
+                path: rel.to_path_buf(),
+                name: new_name,
+            });
+        }
+        rendered.push(new_name);
+    }
+
+    Ok(rendered)
+}
+
+/// Whether a token's `key` looks like a placeholder someone forgot to supply
+/// (`db_name`, `db-url`) rather than other syntax that happens to use the
+/// same delimiters, such as `{{ github.sha }}` or `{{#if x}}`.
+fn is_placeholder_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Why [`substitute`] or [`substitute_stream`] gave up on a file.
+#[derive(Debug)]
+enum SubstituteError {
+    /// The file needs more substitutions than its remaining budget allows.
+    LimitExceeded,
+    /// A placeholder was recognized but its value could not be produced.
+    Invalid(String),
+    /// Reading the input failed.
+    Read(io::Error),
+    /// Writing the output failed.
+    Write(io::Error),
+}
+
+/// Replace every `{{key}}` token (or the same with other `delimiters`) in
+/// `contents` for which `lookup` returns a value, in a single left-to-right
+/// pass. Returns the new contents and the number of substitutions, or `None`
+/// when nothing was replaced. Stops as soon as more than `limit`
+/// substitutions are needed. See [`substitute_stream`] for the matching rules.
+fn substitute(
+    contents: &[u8],
+    delimiters: (&str, &str),
+    lookup: &mut dyn FnMut(&str) -> Option<Result<String, String>>,
+    limit: usize,
+) -> Result<Option<(Vec<u8>, usize)>, SubstituteError> {
+    let mut out = Vec::with_capacity(contents.len());
+    let count = substitute_stream(&mut &contents[..], &mut out, delimiters, lookup, limit)?;
+    Ok((count > 0).then_some((out, count)))
+}
+
+/// Copy `reader` to `writer`, replacing every token for which `lookup`
+/// returns a value, and return the number of substitutions. The input is read
+/// in chunks, so it never has to fit in memory; tokens split across chunks
+/// are still found.
+///
+/// Tokens are matched on their full delimited key, so `{{name}}` never
+/// matches inside `{{namespace}}`, and substituted values are never rescanned
+/// for further tokens. Unknown tokens, and keys longer than [`MAX_KEY_LEN`]
+/// bytes, are left untouched. The scan is over bytes, so the input need not be
+/// UTF-8; only the key inside a token must be.
+fn substitute_stream(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    delimiters: (&str, &str),
+    lookup: &mut dyn FnMut(&str) -> Option<Result<String, String>>,
+    limit: usize,
+) -> Result<usize, SubstituteError> {
+    substitute_chunked(reader, writer, delimiters, lookup, limit, CHUNK_SIZE)
+}
+
+/// [`substitute_stream`], reading `chunk_size` bytes at a time.
+fn substitute_chunked(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    delimiters: (&str, &str),
+    lookup: &mut dyn FnMut(&str) -> Option<Result<String, String>>,
+    limit: usize,
+    chunk_size: usize,
+) -> Result<usize, SubstituteError> {
+    let (open, close) = (delimiters.0.as_bytes(), delimiters.1.as_bytes());
+    // How far past an opening delimiter its closing one may be.
+    let lookahead = open.len() + MAX_KEY_LEN + close.len();
+    let write = |writer: &mut dyn Write, bytes: &[u8]| {
+        writer.write_all(bytes).map_err(SubstituteError::Write)
+    };
+
+    let mut buf = Vec::new();
+    let mut chunk = vec![0; chunk_size];
+    let mut count = 0;
+    let mut eof = false;
+
+    while !eof {
+        let n = loop {
+            match reader.read(&mut chunk) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                read => break read.map_err(SubstituteError::Read)?,
+            }
+        };
+        eof = n == 0;
+        buf.extend_from_slice(&chunk[..n]);
+
+        // Everything before `pos` has been written out; `hold` marks a token
+        // that can only be decided once more input has been read.
+        let mut pos = 0;
+        let mut hold = None;
+        while let Some(start) = find(&buf[pos..], open).map(|i| pos + i) {
+            let after_open = start + open.len();
+            let end = match find(&buf[after_open..buf.len().min(start + lookahead)], close) {
+                None if !eof && buf.len() < start + lookahead => {
+                    hold = Some(start);
+                    break;
+                }
+                end => end,
+            };
+            write(writer, &buf[pos..start])?;
+
+            let value = end.and_then(|end| {
+                let key = std::str::from_utf8(&buf[after_open..after_open + end]).ok()?;
+                Some((end, lookup(key)?))
+            });
+            match value {
+                Some((end, value)) => {
+                    let value = value.map_err(SubstituteError::Invalid)?;
+                    count += 1;
+                    if count > limit {
+                        return Err(SubstituteError::LimitExceeded);
+                    }
+                    write(writer, value.as_bytes())?;
+                    pos = after_open + end + close.len();
+                }
+                None => {
+                    // Not a known token: keep one byte and rescan from the
+                    // next so `{{{{key}}` still finds the inner token.
+                    write(writer, &buf[start..start + 1])?;
+                    pos = start + 1;
+                }
+            }
+        }
+
+        // Without a pending token, only the last few bytes could still turn
+        // out to start an opening delimiter.
+        let flush_to = match hold {
+            Some(start) => start,
+            None if eof => buf.len(),
+            None => buf.len().saturating_sub(open.len() - 1).max(pos),
+        };
+        write(writer, &buf[pos..flush_to])?;
+        buf.drain(..flush_to);
+    }
+
+    Ok(count)
+}
+
+/// Position of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Resolve the fixed timestamp to stamp generated files with, if any.
+///
+/// `SOURCE_DATE_EPOCH` (seconds since the Unix epoch) takes effect whenever it
+/// is set; `deterministic` falls back to the epoch itself when it isn't.
+pub fn reproducible_mtime(deterministic: bool) -> io::Result<Option<SystemTime>> {
+    match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(value) => {
+            let secs: u64 = value.trim().parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("SOURCE_DATE_EPOCH must be a non-negative integer, got \"{value}\""),
+                )
+            })?;
+            Ok(Some(UNIX_EPOCH + Duration::from_secs(secs)))
+        }
+        Err(_) if deterministic => Ok(Some(UNIX_EPOCH)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Set the modification time of every file and directory under `root`
+/// (including `root` itself) to `mtime`.
+pub fn set_mtimes(root: &Path, mtime: SystemTime) -> io::Result<()> {
+    let mtime = FileTime::from_system_time(mtime);
+
+    // Children come before their parents so stamping a file doesn't bump the
+    // directory's mtime after it was set.
+    for entry in WalkDir::new(root).contents_first(true) {
+        let entry = entry.map_err(io::Error::other)?;
+        if entry.path_is_symlink() {
+            continue;
+        }
+        filetime::set_file_mtime(entry.path(), mtime)?;
     }
 
     Ok(())
 }
 
-/// Walk `root` recursively and replace `{{project-name}}` in every regular
-/// file.
-fn replace_placeholders(root: &Path, project_name: &str) -> io::Result<()> {
-    for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+/// Discover available templates under `templates_root`, reading each one's
+/// `template.toml` if present.
+///
+/// Templates can be grouped in category directories, and are then named by
+/// their path relative to the root (`rust/cli`). A directory is a template if
+/// it has a `template.toml`, or if no directory below it has one; otherwise
+/// it is a category, and the directories in it are looked at the same way.
+///
+/// Symlinked directories are followed, whether they are the root itself, a
+/// template or a category; a link back to a category being listed is skipped
+/// rather than followed round in a loop.
+pub fn list_templates(templates_root: &Path) -> Result<Vec<TemplateInfo>, ScaffoldError> {
+    let root = templates_root
+        .canonicalize()
+        .map_err(ScaffoldError::read(templates_root))?;
+    let mut templates = Vec::new();
+    collect_templates(templates_root, None, &mut vec![root], &mut templates)?;
+    templates.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(templates)
+}
+
+/// Add the templates in `dir`, the templates root or the category named
+/// `category`, to `templates`. `listing` holds the canonical paths of `dir`
+/// and the categories it is in.
+fn collect_templates(
+    dir: &Path,
+    category: Option<&str>,
+    listing: &mut Vec<PathBuf>,
+    templates: &mut Vec<TemplateInfo>,
+) -> Result<(), ScaffoldError> {
+    let read = ScaffoldError::read;
+    for entry in fs::read_dir(dir).map_err(read(dir))? {
+        let entry = entry.map_err(read(dir))?;
         let path = entry.path();
-        if !path.is_file() {
+        // `fs::metadata` follows symlinks, so a symlinked template directory
+        // is listed just like a real one.
+        if !fs::metadata(&path).map_err(read(&path))?.is_dir() {
             continue;
         }
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let id = match category {
+            Some(category) => format!("{category}/{name}"),
+            None => name,
+        };
 
-        // Only process files that look like text (skip binary blobs).
-        if let Ok(contents) = fs::read_to_string(path) {
-            if contents.contains("{{project-name}}") {
-                let replaced = contents.replace("{{project-name}}", project_name);
-                fs::write(path, replaced)?;
+        if !path.join(manifest::MANIFEST_FILE).is_file() && has_template_below(&path) {
+            // Canonical paths identify a directory however it is reached, so
+            // a link to one of the categories above this one is a loop.
+            // Linking to the same template twice, as an alias, is fine.
+            let canonical = path.canonicalize().map_err(read(&path))?;
+            if listing.contains(&canonical) {
+                continue;
             }
+            listing.push(canonical);
+            collect_templates(&path, Some(&id), listing, templates)?;
+            listing.pop();
+        } else {
+            templates.push(TemplateInfo {
+                id,
+                manifest: manifest::load(&path)?,
+            });
         }
     }
+    Ok(())
+}
 
+/// Whether any directory below `dir` (not `dir` itself) has a
+/// `template.toml`. Links are followed; `WalkDir` stops at loops.
+fn has_template_below(dir: &Path) -> bool {
+    WalkDir::new(dir)
+        .min_depth(2)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(Result::ok)
+        .any(|entry| entry.file_name() == manifest::MANIFEST_FILE && entry.file_type().is_file())
+}
+
+/// List templates from embedded (compile-time) data, like [`list_templates`].
+pub fn list_templates_embedded(embedded: &Dir) -> Result<Vec<TemplateInfo>, ScaffoldError> {
+    let mut templates = Vec::new();
+    collect_templates_embedded(embedded, embedded, &mut templates)?;
+    templates.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(templates)
+}
+
+/// Add the embedded templates in `dir` to `templates`, like
+/// [`collect_templates`].
+fn collect_templates_embedded(
+    embedded: &Dir,
+    dir: &Dir,
+    templates: &mut Vec<TemplateInfo>,
+) -> Result<(), ScaffoldError> {
+    for subdir in dir.dirs() {
+        // Embedded paths are relative to the templates root, so a
+        // sub-directory's path is its template id.
+        let Some(id) = subdir.path().to_str().map(|id| id.replace('\\', "/")) else {
+            continue;
+        };
+        if !has_embedded_manifest(subdir) && has_embedded_template_below(subdir) {
+            collect_templates_embedded(embedded, subdir, templates)?;
+        } else {
+            templates.push(TemplateInfo {
+                manifest: manifest::load_embedded(embedded, &id)?,
+                id,
+            });
+        }
+    }
     Ok(())
 }
 
-/// Discover available templates by listing sub-directories of `templates_root`.
-pub fn list_templates(templates_root: &Path) -> io::Result<Vec<String>> {
-    let mut templates: Vec<String> = Vec::new();
+/// Whether the embedded directory `dir` has a `template.toml`.
+fn has_embedded_manifest(dir: &Dir) -> bool {
+    dir.get_file(dir.path().join(manifest::MANIFEST_FILE))
+        .is_some()
+}
+
+/// Whether any embedded directory below `dir` has a `template.toml`, like
+/// [`has_template_below`].
+fn has_embedded_template_below(dir: &Dir) -> bool {
+    dir.dirs()
+        .any(|subdir| has_embedded_manifest(subdir) || has_embedded_template_below(subdir))
+}
+
+/// SHA-256 of an embedded template, as lowercase hex.
+///
+/// The hash covers every directory and file under `dir` (including
+/// `template.toml` and `.vibeignore`), in order of their `/`-separated paths
+/// relative to `dir`, so it is the same on every run and platform and changes
+/// whenever a file is added, removed, renamed or edited.
+pub fn template_checksum(dir: &Dir) -> String {
+    let mut entries = Vec::new();
+    collect_embedded(dir, dir.path(), &mut entries);
+
+    let mut entries: Vec<(String, EntryKind)> = entries
+        .into_iter()
+        .map(|(rel, kind)| {
+            let parts: Vec<_> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            (parts.join("/"), kind)
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    // Each entry is a type tag, its path and (for files) its length-prefixed
+    // contents, so no two different trees produce the same byte stream.
+    let mut hasher = Sha256::new();
+    for (path, kind) in &entries {
+        match kind {
+            EntryKind::File {
+                contents: FileContents::Bytes(contents),
+                ..
+            } => {
+                hasher.update(b"f");
+                hasher.update((path.len() as u64).to_le_bytes());
+                hasher.update(path.as_bytes());
+                hasher.update((contents.len() as u64).to_le_bytes());
+                hasher.update(contents);
+            }
+            _ => {
+                hasher.update(b"d");
+                hasher.update((path.len() as u64).to_le_bytes());
+                hasher.update(path.as_bytes());
+            }
+        }
+    }
+
+    hex(hasher)
+}
+
+/// List the files a filesystem template will create, relative to the template
+/// root and sorted by path.
+pub fn template_files(template_dir: &Path) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let ignore = IgnoreRules::load(template_dir)?;
+    let mut files = Vec::new();
 
-    for entry in fs::read_dir(templates_root)? {
+    // Ignored directories are pruned rather than walked, as in `plan`.
+    let walker = WalkDir::new(template_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.path().strip_prefix(template_dir).map_or(true, |rel| {
+                !ignore.is_ignored(rel, entry.file_type().is_dir())
+            })
+        });
+    for entry in walker {
         let entry = entry?;
-        if entry.file_type()?.is_dir() {
-            if let Some(name) = entry.file_name().to_str() {
-                templates.push(name.to_string());
+        if entry.file_type().is_file() || entry.path_is_symlink() {
+            if let Ok(rel) = entry.path().strip_prefix(template_dir) {
+                files.push(rel.to_path_buf());
             }
         }
     }
 
-    templates.sort();
-    Ok(templates)
+    files.sort();
+    Ok(files)
 }
 
-/// List templates from embedded (compile-time) data.
-pub fn list_templates_embedded(embedded: &Dir) -> Vec<String> {
-    let mut templates: Vec<String> = embedded
-        .dirs()
-        .filter_map(|d| d.path().file_name()?.to_str().map(String::from))
-        .collect();
-    templates.sort();
-    templates
+/// List the files an embedded template will create, relative to the template
+/// root and sorted by path.
+pub fn template_files_embedded(
+    embedded: &Dir,
+    template_name: &str,
+) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let template_dir = embedded
+        .get_dir(template_name)
+        .ok_or_else(|| ScaffoldError::TemplateNotFound(template_name.to_string()))?;
+
+    let mut files = Vec::new();
+    collect_embedded_files(template_dir, Path::new(template_name), &mut files);
+    let ignore = IgnoreRules::load_embedded(embedded, template_name)?;
+    files.retain(|rel| !ignore.is_ignored(rel, false));
+    files.sort();
+    Ok(files)
+}
+
+/// Recursively collect the paths of an embedded directory, relative to `root`.
+fn collect_embedded_files(dir: &Dir, root: &Path, files: &mut Vec<PathBuf>) {
+    for file in dir.files() {
+        if let Ok(rel) = file.path().strip_prefix(root) {
+            files.push(rel.to_path_buf());
+        }
+    }
+
+    for subdir in dir.dirs() {
+        collect_embedded_files(subdir, root, files);
+    }
 }
 
 /// Resolve the absolute path to a template directory.
+///
+/// The path is canonicalized when possible so a symlinked template is copied
+/// from its real location rather than through the link.
 pub fn resolve_template_dir(templates_root: &Path, template_name: &str) -> PathBuf {
-    templates_root.join(template_name)
+    // Names of nested templates (`rust/cli`) always use `/`.
+    let dir = template_name
+        .split('/')
+        .fold(templates_root.to_path_buf(), |dir, name| dir.join(name));
+    dir.canonicalize().unwrap_or(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `contents` to `root/rel`, creating its parent directories.
+    fn write(root: &Path, rel: &str, contents: impl AsRef<[u8]>) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn ids(templates: &[TemplateInfo]) -> Vec<&str> {
+        templates.iter().map(|t| t.id.as_str()).collect()
+    }
+
+    /// Substitute `values` into `contents`, with the default delimiters and
+    /// no limit.
+    fn render(contents: &str, values: &[(&str, &str)]) -> String {
+        let values: HashMap<&str, &str> = values.iter().copied().collect();
+        let mut lookup = |key: &str| values.get(key).map(|value| Ok(value.to_string()));
+        let mut out = Vec::new();
+        substitute_stream(
+            &mut contents.as_bytes(),
+            &mut out,
+            manifest::DEFAULT_DELIMITERS,
+            &mut lookup,
+            usize::MAX,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn splits_names_into_words() {
+        for (name, words) in [
+            ("HTTPServer", &["http", "server"][..]),
+            ("my2App", &["my2", "app"]),
+            ("v2-api", &["v2", "api"]),
+            ("app2go", &["app2go"]),
+            ("my--app", &["my", "app"]),
+            ("my app", &["my", "app"]),
+            ("my_app", &["my", "app"]),
+            ("_app_", &["app"]),
+        ] {
+            assert_eq!(split_words(name), words, "{name}");
+        }
+
+        let placeholders = build_placeholders("HTTPServer2Go");
+        assert_eq!(placeholders["project-name-kebab"], "http-server2-go");
+        assert_eq!(placeholders["project-name-screaming"], "HTTP_SERVER2_GO");
+        let placeholders = build_placeholders("my--app v2");
+        assert_eq!(placeholders["project-name-kebab"], "my-app-v2");
+        assert_eq!(placeholders["project-name-screaming"], "MY_APP_V2");
+    }
+
+    #[test]
+    fn year_follows_source_date_epoch() {
+        // 2024-12-31T23:59:59Z
+        let now = UNIX_EPOCH + Duration::from_secs(1_735_689_599);
+        // 2000-06-01T00:00:00Z
+        assert_eq!(year_at(Some("959817600"), now), 2000);
+        assert_eq!(year_at(Some(" 959817600\n"), now), 2000);
+        for invalid in [None, Some(""), Some("soon"), Some("-1")] {
+            assert_eq!(year_at(invalid, now), 2024, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn keys_match_whole_tokens_only() {
+        assert_eq!(
+            render("{{name}} {{namespace}} {{ name }}", &[("name", "app")]),
+            "app {{namespace}} {{ name }}"
+        );
+        assert_eq!(
+            render("{{{{name}}}} {name}", &[("name", "app")]),
+            "{{app}} {name}"
+        );
+    }
+
+    #[test]
+    fn substituted_values_are_not_rescanned() {
+        let values = [
+            ("name", "{{other}}"),
+            ("other", "oops"),
+            ("self", "{{self}}"),
+        ];
+        assert_eq!(render("{{name}} {{self}}", &values), "{{other}} {{self}}");
+        // A value can't complete a token with the text around it either.
+        assert_eq!(render("{{open}}name}}", &[("open", "{{")]), "{{name}}");
+    }
+
+    #[test]
+    fn finds_tokens_split_across_chunks() {
+        let values: HashMap<&str, &str> = [("name", "app"), ("x", "y")].into_iter().collect();
+        let contents = "{{name}}-{{x}} {{nope}} {{{{name}}}}\n<%name%> {{name}}";
+        for (delimiters, expected) in [
+            (
+                manifest::DEFAULT_DELIMITERS,
+                "app-y {{nope}} {{app}}\n<%name%> app",
+            ),
+            (
+                ("<%", "%>"),
+                "{{name}}-{{x}} {{nope}} {{{{name}}}}\napp {{name}}",
+            ),
+        ] {
+            // Every chunk size up to the whole input puts a boundary inside
+            // each delimiter and key at some point.
+            for chunk_size in 1..=contents.len() {
+                let mut lookup = |key: &str| values.get(key).map(|value| Ok(value.to_string()));
+                let mut out = Vec::new();
+                substitute_chunked(
+                    &mut contents.as_bytes(),
+                    &mut out,
+                    delimiters,
+                    &mut lookup,
+                    usize::MAX,
+                    chunk_size,
+                )
+                .unwrap();
+                assert_eq!(
+                    String::from_utf8(out).unwrap(),
+                    expected,
+                    "chunk size {chunk_size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn streams_files_over_the_threshold() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        let mut contents = vec![b'.'; 3 * STREAM_THRESHOLD as usize];
+        // One token at the start, two straddling chunk boundaries (one split
+        // inside the opening delimiter) and one at the very end.
+        let name = b"{{project-name}}";
+        let secret = b"{{secret:16:key}}";
+        let end = contents.len() - name.len();
+        for (at, token) in [
+            (0, &name[..]),
+            (CHUNK_SIZE - 5, name),
+            (4 * CHUNK_SIZE - 1, secret),
+            (end, name),
+        ] {
+            contents[at..at + token.len()].copy_from_slice(token);
+        }
+        write(&template, "big.txt", &contents);
+
+        let mut options = ScaffoldOptions::new("app", temp.path().join("out"));
+        options.secret_seed = Some(7);
+        let plan = plan(&template, &options).unwrap();
+        assert!(matches!(plan.files[0].contents, FileContents::Streamed(_)));
+        assert_eq!(plan.files[0].substitutions["project-name"], 3);
+        assert_eq!(plan.files[0].substitutions["secret:16:key"], 1);
+        let report = plan.execute(&mut |_, _, _| {}).unwrap();
+        assert_eq!(report.substitutions, 4);
+        assert_eq!(report.files[0].substitutions, 4);
+
+        let written = fs::read(temp.path().join("out/app/big.txt")).unwrap();
+        let secret = SecretGenerator::new(Some(7))
+            .resolve("secret:16:key")
+            .unwrap()
+            .unwrap();
+        let expected = String::from_utf8(contents)
+            .unwrap()
+            .replace("{{project-name}}", "app")
+            .replace("{{secret:16:key}}", &secret);
+        assert_eq!(written, expected.as_bytes());
+    }
+
+    #[test]
+    fn vibeignore_supports_globs_negation_and_directory_patterns() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        write(
+            &template,
+            ".vibeignore",
+            "**/*.log\n!keep.log\nbuild/\ndocs/**/draft.md\n",
+        );
+        for rel in [
+            "a.log",
+            "keep.log",
+            "src/b.log",
+            "src/keep.log",
+            "build/out.txt",
+            "build/nested/keep.log",
+            "notes/build",
+            "docs/guide/draft.md",
+            "docs/guide/index.md",
+            "README.md",
+        ] {
+            write(&template, rel, "x\n");
+        }
+
+        let expected = [
+            "README.md",
+            "docs/guide/index.md",
+            "keep.log",
+            "notes/build",
+            "src/keep.log",
+        ]
+        .map(PathBuf::from);
+        // A file in an ignored directory stays out even if negated.
+        assert_eq!(template_files(&template).unwrap(), expected);
+
+        let options = ScaffoldOptions::new("app", temp.path().join("out"));
+        let plan = plan(&template, &options).unwrap();
+        let mut planned: Vec<_> = plan.files.iter().map(|f| f.path.clone()).collect();
+        planned.sort();
+        assert_eq!(planned, expected);
+        assert!(!plan.dirs.contains(&PathBuf::from("build")));
+    }
+
+    #[test]
+    fn built_in_placeholders_include_the_default_version() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        write(&template, "Cargo.toml", "version = \"{{version}}\"\n");
+
+        let mut options = ScaffoldOptions::new("app", temp.path().join("out"));
+        options.strict = true;
+        scaffold(&template, &options, &mut |_, _, _| {}).unwrap();
+        assert_eq!(
+            fs::read_to_string(temp.path().join("out/app/Cargo.toml")).unwrap(),
+            format!("version = \"{DEFAULT_VERSION}\"\n")
+        );
+    }
+
+    #[test]
+    fn substitution_stops_as_soon_as_the_limit_is_exceeded() {
+        let contents = "{{x}}".repeat(10);
+        let mut lookups = 0;
+        let mut lookup = |_: &str| {
+            lookups += 1;
+            Some(Ok("y".to_string()))
+        };
+        let mut out = Vec::new();
+        let result = substitute_stream(
+            &mut contents.as_bytes(),
+            &mut out,
+            manifest::DEFAULT_DELIMITERS,
+            &mut lookup,
+            3,
+        );
+        assert!(matches!(result, Err(SubstituteError::LimitExceeded)));
+        assert_eq!(lookups, 4);
+        assert_eq!(out, b"yyy");
+    }
+
+    #[test]
+    fn too_many_substitutions_names_the_file_that_crossed_the_limit() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        write(&template, "a.txt", "{{project-name}} {{project-name}}\n");
+        write(&template, "b.txt", "{{project-name}}".repeat(3));
+        write(&template, "c.txt", "{{project-name}}\n");
+
+        let mut options = ScaffoldOptions::new("app", temp.path().join("out"));
+        options.max_substitutions = 4;
+        match plan(&template, &options) {
+            Err(ScaffoldError::TooManySubstitutions { limit, path }) => {
+                assert_eq!(limit, 4);
+                assert_eq!(path, Path::new("b.txt"));
+            }
+            Err(e) => panic!("expected TooManySubstitutions, got {e:?}"),
+            Ok(_) => panic!("expected TooManySubstitutions"),
+        }
+        assert!(!temp.path().join("out").exists());
+    }
+
+    /// Contents of the files under `root`, by path.
+    fn read_tree(root: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+        template_files(root)
+            .unwrap()
+            .into_iter()
+            .map(|rel| {
+                let contents = fs::read(root.join(&rel)).unwrap();
+                (rel, contents)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_substitution_is_deterministic() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        for i in 0..400 {
+            write(
+                &template,
+                &format!("src/file{i:03}.txt"),
+                format!(
+                    "{{{{project-name}}}} {{{{secret:32:key{}}}}} {{{{password:16}}}}\n",
+                    i % 7
+                ),
+            );
+        }
+
+        let run = |out: &str| {
+            let mut options = ScaffoldOptions::new("app", temp.path().join(out));
+            options.secret_seed = Some(42);
+            let report = scaffold(&template, &options, &mut |_, _, _| {}).unwrap();
+            assert_eq!(report.substitutions, 1200);
+            read_tree(&temp.path().join(out).join("app"))
+        };
+        let first = run("out1");
+        assert_eq!(first.len(), 400);
+        for out in ["out2", "out3"] {
+            assert_eq!(run(out), first);
+        }
+        // One value per token, whichever file it was resolved in first.
+        let file = |i: usize| &first[&PathBuf::from(format!("src/file{i:03}.txt"))];
+        assert_eq!(file(0), file(7));
+        assert_ne!(file(0), file(1));
+
+        // Several files fail; the first of them in template order is reported.
+        for i in [150, 151, 300, 399] {
+            write(&template, &format!("src/file{i:03}.txt"), "{{secret:8}}\n");
+        }
+        for _ in 0..5 {
+            let options = ScaffoldOptions::new("app", temp.path().join("out4"));
+            match plan(&template, &options) {
+                Err(ScaffoldError::PlaceholderRewrite { path, .. }) => {
+                    assert_eq!(path, Path::new("src/file150.txt"));
+                }
+                Err(e) => panic!("expected PlaceholderRewrite, got {e:?}"),
+                Ok(_) => panic!("expected PlaceholderRewrite"),
+            }
+        }
+    }
+
+    #[test]
+    fn substitutes_into_files_that_are_not_utf8() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        // Latin-1: "café {{project-name}} ÿ", plus a lone continuation byte.
+        write(
+            &template,
+            "notes.txt",
+            b"caf\xe9 {{project-name}} \xff\x80\n",
+        );
+
+        let options = ScaffoldOptions::new("app", temp.path().join("out"));
+        let report = scaffold(&template, &options, &mut |_, _, _| {}).unwrap();
+        assert_eq!(report.substitutions, 1);
+        assert_eq!(
+            fs::read(temp.path().join("out/app/notes.txt")).unwrap(),
+            b"caf\xe9 app \xff\x80\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn recreates_template_symlinks_as_links() {
+        use std::os::unix::fs::symlink;
+
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        write(&template, "README.md", "# {{project-name}}\n");
+        write(&template, "docs/guide.md", "guide\n");
+        symlink("README.md", template.join("README")).unwrap();
+        symlink("../README.md", template.join("docs/README.md")).unwrap();
+        symlink("missing", template.join("dangling")).unwrap();
+
+        let options = ScaffoldOptions::new("app", temp.path().join("out"));
+        let report = scaffold(&template, &options, &mut |_, _, _| {}).unwrap();
+        assert!(report.skipped_links.is_empty());
+
+        let project = temp.path().join("out/app");
+        for (link, target) in [
+            ("README", "README.md"),
+            ("docs/README.md", "../README.md"),
+            ("dangling", "missing"),
+        ] {
+            let path = project.join(link);
+            assert!(path.symlink_metadata().unwrap().is_symlink(), "{link}");
+            assert_eq!(fs::read_link(&path).unwrap(), Path::new(target));
+        }
+    }
+
+    #[test]
+    fn creates_a_missing_nested_output_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        write(&template, "README.md", "# {{project-name}}\n");
+
+        let output_dir = temp.path().join("output/a/b");
+        let options = ScaffoldOptions::new("app", &output_dir);
+        scaffold(&template, &options, &mut |_, _, _| {}).unwrap();
+        assert_eq!(
+            fs::read_to_string(output_dir.join("app/README.md")).unwrap(),
+            "# app\n"
+        );
+        let entries: Vec<_> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, ["app"]);
+    }
+
+    #[test]
+    fn a_failed_write_removes_only_the_directories_it_created() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        for i in 0..20 {
+            write(
+                &template,
+                &format!("src/file{i:02}.txt"),
+                "{{project-name}}\n",
+            );
+        }
+        let existing = temp.path().join("existing");
+        write(&existing, "keep.txt", "mine\n");
+
+        let options = ScaffoldOptions::new("app", existing.join("new/deeper"));
+        let mut plan = plan(&template, &options).unwrap();
+        // No file system accepts a name this long, so this write fails after
+        // some of the others have succeeded.
+        plan.files.insert(
+            10,
+            PlannedFile {
+                path: PathBuf::from("src").join("x".repeat(300)),
+                contents: FileContents::Bytes(Vec::new()),
+                substitutions: BTreeMap::new(),
+                executable: false,
+            },
+        );
+
+        let result = plan.execute(&mut |_, _, _| {});
+        assert!(matches!(result, Err(ScaffoldError::CopyFailed { .. })));
+        assert!(!existing.join("new").exists());
+        let entries: Vec<_> = fs::read_dir(&existing)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, ["keep.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lists_templates_through_a_symlinked_root_without_looping() {
+        use std::os::unix::fs::symlink;
+
+        let temp = tempfile::tempdir().unwrap();
+        let real = temp.path().join("real");
+        write(&real, "rust/cli/template.toml", "description = \"A CLI\"\n");
+        write(&real, "rust/cli/src/main.rs", "fn main() {}\n");
+        write(&real, "web/index.html", "<p>{{project-name}}</p>\n");
+        // An alias of a template, and a link from a category back to the root.
+        symlink("cli", real.join("rust/cli-latest")).unwrap();
+        symlink("../..", real.join("rust/loop")).unwrap();
+        let root = temp.path().join("templates");
+        symlink(&real, &root).unwrap();
+
+        let templates = list_templates(&root).unwrap();
+        assert_eq!(ids(&templates), ["rust/cli", "rust/cli-latest", "web"]);
+        assert_eq!(templates[0].manifest.description.as_deref(), Some("A CLI"));
+
+        let template_dir = resolve_template_dir(&root, "rust/cli");
+        assert_eq!(template_dir, real.canonicalize().unwrap().join("rust/cli"));
+        let options = ScaffoldOptions::new("app", temp.path().join("out"));
+        scaffold(&template_dir, &options, &mut |_, _, _| {}).unwrap();
+        assert!(temp.path().join("out/app/src/main.rs").is_file());
+    }
+
+    #[test]
+    fn renders_placeholder_directories_and_their_children() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        write(
+            &template,
+            "{{project-name}}/{{project-name-snake}}.rs",
+            "// {{project-name}}\n",
+        );
+        write(&template, "{{project-name}}/docs/README.md", "docs\n");
+
+        let options = ScaffoldOptions::new("my-app", temp.path().join("out"));
+        let plan = plan(&template, &options).unwrap();
+        let files: Vec<_> = plan.files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(
+            files,
+            [
+                Path::new("my-app/docs/README.md"),
+                Path::new("my-app/my_app.rs")
+            ]
+        );
+        assert_eq!(
+            plan.renamed[Path::new("my-app/my_app.rs")],
+            Path::new("{{project-name}}/{{project-name-snake}}.rs")
+        );
+        assert!(plan.unresolved.is_empty());
+
+        plan.execute(&mut |_, _, _| {}).unwrap();
+        assert_eq!(
+            fs::read_to_string(temp.path().join("out/my-app/my-app/my_app.rs")).unwrap(),
+            "// my-app\n"
+        );
+    }
+
+    #[test]
+    fn paths_rendering_to_the_same_name_collide() {
+        let temp = tempfile::tempdir().unwrap();
+        let template = temp.path().join("template");
+        write(&template, "{{project-name}}.txt", "rendered\n");
+        write(&template, "app.txt", "literal\n");
+
+        let options = ScaffoldOptions::new("app", temp.path().join("out"));
+        match plan(&template, &options) {
+            Err(ScaffoldError::PathCollision {
+                first,
+                second,
+                target,
+            }) => {
+                assert_eq!(target, Path::new("app.txt"));
+                let mut sources = [first, second];
+                sources.sort();
+                assert_eq!(
+                    sources,
+                    [
+                        PathBuf::from("app.txt"),
+                        PathBuf::from("{{project-name}}.txt")
+                    ]
+                );
+            }
+            Err(e) => panic!("expected PathCollision, got {e:?}"),
+            Ok(_) => panic!("expected PathCollision"),
+        }
+        assert!(!temp.path().join("out").exists());
+    }
 }
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand::rngs::{OsRng, StdRng};
+use rand::{Rng, RngCore, SeedableRng};
+
+/// Allowed byte counts for `{{secret:N}}`.
+const SECRET_BYTES: RangeInclusive<usize> = 16..=1024;
+
+/// Allowed lengths for `{{password:N}}`.
+const PASSWORD_CHARS: RangeInclusive<usize> = 8..=256;
+
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+const SYMBOLS: &[u8] = b"!#%+-.:=?@^_~";
+
+/// Generates values for the secret placeholders:
+///
+/// - `{{secret:N}}` — `N` random bytes, base64-encoded,
+/// - `{{password:N}}` — an `N`-character password mixing lower and upper
+///   case letters, digits and symbols.
+///
+/// Either form may carry a label (`{{secret:32:jwt}}`). Each distinct token
+/// gets one value per run, so repeating a token (e.g. in `.env` and a compose
+/// file) yields the same secret, while different labels yield different ones.
+pub struct SecretGenerator {
+    seed: Option<u64>,
+    generated: HashMap<String, String>,
+}
+
+impl SecretGenerator {
+    /// Create a generator backed by the operating system's CSPRNG, or by a
+    /// seeded PRNG when `seed` is given. Seeded secrets are reproducible and
+    /// therefore insecure; callers must warn loudly when using one.
+    pub fn new(seed: Option<u64>) -> Self {
+        Self {
+            seed,
+            generated: HashMap::new(),
+        }
+    }
+
+    /// The random source for `key`. A seeded generator derives a separate
+    /// PRNG for every token, so each value depends only on the seed and the
+    /// token, not on the order in which files are processed.
+    fn rng(&self, key: &str) -> Box<dyn RngCore> {
+        match self.seed {
+            Some(seed) => Box::new(StdRng::seed_from_u64(seed ^ fnv1a(key.as_bytes()))),
+            None => Box::new(OsRng),
+        }
+    }
+
+    /// Resolve `key` if it names a secret placeholder. Returns `None` for other
+    /// keys and an error message for a malformed or out-of-range length.
+    pub fn resolve(&mut self, key: &str) -> Option<Result<String, String>> {
+        let (kind, rest) = key.split_once(':')?;
+        if kind != "secret" && kind != "password" {
+            return None;
+        }
+
+        if let Some(value) = self.generated.get(key) {
+            return Some(Ok(value.clone()));
+        }
+
+        let len_str = rest.split_once(':').map_or(rest, |(len, _label)| len);
+        let (range, unit) = if kind == "secret" {
+            (SECRET_BYTES, "bytes")
+        } else {
+            (PASSWORD_CHARS, "characters")
+        };
+        let len = match len_str.parse::<usize>() {
+            Ok(len) if range.contains(&len) => len,
+            _ => {
+                return Some(Err(format!(
+                    "Invalid placeholder {{{{{key}}}}}: length must be {}-{} {unit}",
+                    range.start(),
+                    range.end()
+                )))
+            }
+        };
+
+        let mut rng = self.rng(key);
+        let value = if kind == "secret" {
+            let mut bytes = vec![0u8; len];
+            rng.fill_bytes(&mut bytes);
+            STANDARD.encode(bytes)
+        } else {
+            password(&mut *rng, len)
+        };
+
+        self.generated.insert(key.to_string(), value.clone());
+        Some(Ok(value))
+    }
+}
+
+/// Generate a password of `len` characters containing at least one character
+/// from every class.
+fn password(rng: &mut dyn RngCore, len: usize) -> String {
+    let alphabet: Vec<u8> = [LOWER, UPPER, DIGITS, SYMBOLS].concat();
+    loop {
+        let candidate: Vec<u8> = (0..len)
+            .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+            .collect();
+        let complete = [LOWER, UPPER, DIGITS, SYMBOLS]
+            .iter()
+            .all(|class| candidate.iter().any(|c| class.contains(c)));
+        if complete {
+            return String::from_utf8(candidate).expect("alphabet is ASCII");
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash: small and, unlike `DefaultHasher`, guaranteed stable
+/// across Rust releases, which seeded secrets rely on.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
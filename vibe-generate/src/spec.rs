@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// A declarative generation recipe, equivalent to passing the same values as
+/// command-line flags.
+///
+/// ```toml
+/// template = "rust-1.9"
+/// name = "my-cli"
+/// output_dir = "projects"
+///
+/// [vars]
+/// author = "Jane Doe"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spec {
+    pub template: Option<String>,
+    pub name: Option<String>,
+    /// Resolved relative to the directory containing the spec file.
+    pub output_dir: Option<PathBuf>,
+    /// Extra placeholder values, substituted for `{{key}}`.
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+/// Read and parse a spec file.
+pub fn load(path: &Path) -> io::Result<Spec> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("Failed to read spec file {}: {e}", path.display()),
+        )
+    })?;
+
+    let mut spec: Spec = toml::from_str(&contents).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid spec file {}: {e}", path.display()),
+        )
+    })?;
+
+    if let (Some(dir), Some(base)) = (spec.output_dir.as_mut(), path.parent()) {
+        if dir.is_relative() {
+            *dir = base.join(&*dir);
+        }
+    }
+
+    Ok(spec)
+}
@@ -0,0 +1,73 @@
+/// Number of single-character insertions, deletions and substitutions needed
+/// to turn `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// The candidate closest to `input`, if it is close enough to be a likely
+/// typo: within one edit per three characters of `input`, and at most three.
+/// Ties go to the earliest candidate.
+pub fn closest<'a>(input: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let threshold = (input.chars().count() / 3).clamp(1, 3);
+    candidates
+        .iter()
+        .map(|c| (levenshtein(input, c), c))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn counts_edits() {
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("rust-1.9", "rust-1.9"), 0);
+    }
+
+    #[test]
+    fn suggests_a_close_typo() {
+        let templates = names(&["java-25", "nextjs", "rust-1.9"]);
+        assert_eq!(closest("rust-1.8", &templates), Some("rust-1.9"));
+        assert_eq!(closest("nxtjs", &templates), Some("nextjs"));
+        assert_eq!(closest("java25", &templates), Some("java-25"));
+    }
+
+    #[test]
+    fn suggests_nothing_far_off() {
+        let templates = names(&["java-25", "nextjs", "rust-1.9"]);
+        assert_eq!(closest("python", &templates), None);
+        // Two characters allow only one edit.
+        assert_eq!(closest("js", &names(&["go"])), None);
+        assert_eq!(closest("anything", &[]), None);
+    }
+
+    #[test]
+    fn ties_go_to_the_earliest_candidate() {
+        assert_eq!(closest("hat", &names(&["cat", "bat"])), Some("cat"));
+        assert_eq!(closest("hat", &names(&["bat", "cat"])), Some("bat"));
+    }
+}
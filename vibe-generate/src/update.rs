@@ -0,0 +1,194 @@
+use std::env;
+use std::error::Error;
+use std::path::Path;
+use std::time::SystemTime;
+
+use console::Style;
+
+use vibe_generate::manifest;
+use vibe_generate::scaffold::{
+    build_placeholders, plan, plan_embedded, plan_update, resolve_template_dir, ScaffoldOptions,
+};
+use vibe_generate::{ProjectManifest, PROJECT_MANIFEST_FILE};
+
+use crate::cli::UpdateArgs;
+use crate::error::CliError;
+use crate::{
+    check_features, config, config_vars, find_templates_root, gitignore, messages,
+    resolve_declared, resolve_features, TemplateSource, EMBEDDED_TEMPLATES,
+};
+
+/// `vibe-generate update`: re-apply a template over an existing project,
+/// writing only new files and files unchanged since they were generated.
+pub fn run(args: &UpdateArgs) -> Result<(), Box<dyn Error>> {
+    let bold = Style::new().bold();
+    let green = Style::new().green().bold();
+    let yellow = Style::new().yellow().bold();
+
+    let user_config = config::load();
+    let output_dir = match (&args.output_dir, &user_config.output_dir) {
+        (Some(dir), _) | (None, Some(dir)) => dir.clone(),
+        (None, None) => env::current_dir()?,
+    };
+    let project_dir = output_dir.join(&args.name);
+    if !project_dir.is_dir() {
+        return Err(CliError::localized(
+            "project_not_found",
+            "error.update-not-a-project",
+            &[("path", &project_dir.display())],
+        )
+        .into());
+    }
+
+    let recorded = ProjectManifest::load(&project_dir)?;
+    let template = match (&args.template, &recorded) {
+        (Some(template), _) => template.clone(),
+        (None, Some(recorded)) => recorded.template.clone(),
+        (None, None) => {
+            return Err(CliError::localized(
+                "no_project_manifest",
+                "error.update-no-manifest",
+                &[
+                    ("path", &project_dir.display()),
+                    ("manifest", &PROJECT_MANIFEST_FILE),
+                ],
+            )
+            .into())
+        }
+    };
+    if recorded.is_none() {
+        eprintln!(
+            "{} {}",
+            yellow.apply_to(messages::get("label.warning")),
+            messages::fill(
+                "warning.update-no-manifest",
+                &[
+                    ("path", &project_dir.display()),
+                    ("manifest", &PROJECT_MANIFEST_FILE),
+                ],
+            )
+        );
+    }
+
+    let source = match find_templates_root() {
+        Some(root) => TemplateSource::Filesystem(root),
+        None => TemplateSource::Embedded,
+    };
+    let template_manifest = match &source {
+        TemplateSource::Filesystem(root) => manifest::load(&resolve_template_dir(root, &template))?,
+        TemplateSource::Embedded => manifest::load_embedded(&EMBEDDED_TEMPLATES, &template)?,
+    };
+
+    // The placeholder values recorded when generating, so unchanged template
+    // files render exactly as they did then.
+    let mut placeholders = build_placeholders(&args.name);
+    placeholders.insert("template-source".to_string(), source.provenance(&template));
+    placeholders.extend(config_vars(&user_config, &template_manifest.placeholders));
+    if let Some(recorded) = &recorded {
+        placeholders.extend(recorded.placeholders.clone());
+    }
+    if let Some(version) = &args.project_version {
+        placeholders.insert("version".to_string(), version.clone());
+    }
+    placeholders.extend(args.set.iter().cloned());
+    resolve_declared(
+        &template_manifest.placeholders,
+        &mut placeholders,
+        &user_config.vars,
+    )?;
+    // Features declined when generating stay off; new ones need --feature.
+    let features = match &recorded {
+        Some(recorded) => {
+            check_features(&template, &template_manifest.features, &args.features)?;
+            let mut features = recorded.features.clone();
+            features.extend(args.features.iter().cloned());
+            features
+        }
+        None => resolve_features(&template, &template_manifest.features, &args.features)?,
+    };
+
+    let mut options = ScaffoldOptions::new(&args.name, &output_dir);
+    options.placeholders = placeholders;
+    options.features = features;
+    options.force = true;
+    let plan = match &source {
+        TemplateSource::Filesystem(root) => plan(&resolve_template_dir(root, &template), &options)?,
+        TemplateSource::Embedded => plan_embedded(&EMBEDDED_TEMPLATES, &template, &options)?,
+    };
+    let update = plan_update(plan, recorded.as_ref())?;
+
+    let status = if args.dry_run {
+        "status.updating-dry-run"
+    } else {
+        "status.updating"
+    };
+    println!(
+        "{} {}",
+        bold.apply_to("=>"),
+        messages::fill(
+            status,
+            &[
+                ("path", &green.apply_to(project_dir.display())),
+                ("template", &green.apply_to(&template)),
+            ],
+        )
+    );
+    let sections = [
+        (messages::get("update.add"), &update.added),
+        (messages::get("update.update"), &update.updated),
+        (messages::get("update.conflict"), &update.conflicts),
+        (messages::get("update.keep"), &update.kept_secrets),
+    ];
+    let width = sections
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (label, paths) in &sections {
+        for path in paths.iter() {
+            println!("  {label:width$}  {}", path.display());
+        }
+    }
+    println!(
+        "\n  {}",
+        messages::fill(
+            "status.update-summary",
+            &[
+                ("added", &update.added.len()),
+                ("updated", &update.updated.len()),
+                ("unchanged", &update.unchanged.len()),
+                ("conflicts", &update.conflicts.len()),
+                ("kept", &update.kept_secrets.len()),
+            ],
+        )
+    );
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    update.plan.execute(&mut |_, _, _| {})?;
+    let gitignore = recorded.as_ref().and_then(|r| r.gitignore.as_deref());
+    if let Some(language) = gitignore {
+        gitignore::apply(&project_dir, language)?;
+    }
+
+    // Record what the project now has from the template. Conflicting files
+    // keep their old hashes, so they still count as changed next time.
+    let mut manifest = recorded.unwrap_or_default();
+    manifest.set_run(&template, &options, SystemTime::now());
+    let current = update.plan.files.iter().map(|f| f.path.as_path());
+    for path in current.chain(update.unchanged.iter().map(Path::new)) {
+        manifest.update_hash(&project_dir, path)?;
+    }
+    manifest.write(&project_dir)?;
+
+    if !update.conflicts.is_empty() {
+        println!(
+            "\n{} {}",
+            yellow.apply_to(messages::get("label.warning")),
+            messages::get("warning.update-conflicts"),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use include_dir::Dir;
+
+use crate::manifest::MANIFEST_FILE;
+use crate::scaffold::ScaffoldError;
+
+/// File name of the optional ignore file at a template's root.
+pub const IGNORE_FILE: &str = ".vibeignore";
+
+/// Which template paths are left out of generated projects: those matching
+/// the template's `.vibeignore` (gitignore syntax, including `**/` and `!`
+/// negation, relative to the template root), plus the `.vibeignore` and
+/// `template.toml` files themselves.
+pub struct IgnoreRules(Gitignore);
+
+impl IgnoreRules {
+    /// Load the rules of the filesystem template at `template_dir`.
+    pub fn load(template_dir: &Path) -> Result<Self, ScaffoldError> {
+        let path = template_dir.join(IGNORE_FILE);
+        match fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents, &path),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::parse("", &path),
+            Err(source) => Err(ScaffoldError::ReadFailed { path, source }),
+        }
+    }
+
+    /// Load the rules of an embedded template.
+    pub fn load_embedded(embedded: &Dir, template_name: &str) -> Result<Self, ScaffoldError> {
+        let path = Path::new(template_name).join(IGNORE_FILE);
+        let contents = embedded
+            .get_file(&path)
+            .and_then(|file| file.contents_utf8())
+            .unwrap_or_default();
+        Self::parse(contents, &path)
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<Self, ScaffoldError> {
+        // Patterns are matched against paths relative to the template root,
+        // so the builder's root is empty.
+        let mut builder = GitignoreBuilder::new("");
+        for line in contents.lines() {
+            builder
+                .add_line(Some(path.to_path_buf()), line)
+                .map_err(|e| ScaffoldError::InvalidIgnoreFile {
+                    path: path.to_path_buf(),
+                    message: e.to_string(),
+                })?;
+        }
+        let rules = builder
+            .build()
+            .map_err(|e| ScaffoldError::InvalidIgnoreFile {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })?;
+        Ok(Self(rules))
+    }
+
+    /// Whether the template entry at `rel` (relative to the template root)
+    /// is left out, either itself or because a parent directory is.
+    pub fn is_ignored(&self, rel: &Path, is_dir: bool) -> bool {
+        rel == Path::new(IGNORE_FILE)
+            || rel == Path::new(MANIFEST_FILE)
+            || self.0.matched_path_or_any_parents(rel, is_dir).is_ignore()
+    }
+}
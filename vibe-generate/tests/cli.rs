@@ -0,0 +1,364 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use serde_json::Value;
+
+/// `vibe-generate` run in `cwd`, isolated from the environment of the test
+/// run: no `cargo run` manifest directory to find `templates/` from, no user
+/// config, English messages and nothing interactive.
+fn vibe(cwd: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_vibe-generate"));
+    command
+        .current_dir(cwd)
+        .env_remove("CARGO_MANIFEST_DIR")
+        .env("XDG_CONFIG_HOME", cwd.join(".config"))
+        .env("LANG", "C")
+        .env("CI", "1")
+        .stdin(Stdio::null());
+    command
+}
+
+fn run(command: &mut Command) -> Output {
+    command.output().expect("failed to run vibe-generate")
+}
+
+fn stdout_json(output: &Output) -> Value {
+    serde_json::from_slice(&output.stdout).expect("stdout is not JSON")
+}
+
+/// Write `contents` to `root/rel`, creating its parent directories.
+fn write(root: &Path, rel: &str, contents: &str) {
+    let path = root.join(rel);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+/// Commit everything in `dir` to a new git repository there.
+fn git_repo(dir: &Path) {
+    for args in [
+        &["init", "-q"][..],
+        &["add", "-A"],
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-qm",
+            "t",
+        ],
+    ] {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
+
+#[test]
+fn falls_back_to_embedded_templates_unless_no_embedded() {
+    let temp = tempfile::tempdir().unwrap();
+
+    let output = run(vibe(temp.path()).args(["--list", "--no-embedded"]));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--no-embedded"));
+
+    let output = run(vibe(temp.path()).args(["--list", "--format", "json"]));
+    assert!(output.status.success());
+    let templates = stdout_json(&output);
+    let ids: Vec<&str> = templates
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["java-25", "nextjs", "rust-1.9"]);
+}
+
+#[test]
+fn spec_vars_win_over_built_ins_and_lose_to_set() {
+    let temp = tempfile::tempdir().unwrap();
+    write(
+        temp.path(),
+        "templates/card/README.md",
+        "{{project-name}} by {{author}}, v{{version}}\n",
+    );
+    write(
+        temp.path(),
+        "project.toml",
+        "template = \"card\"\nname = \"app\"\noutput_dir = \"out\"\n\n\
+         [vars]\nauthor = \"Jane Doe\"\nversion = \"2.0.0\"\n",
+    );
+
+    let output = run(vibe(temp.path()).args(["--spec", "project.toml"]));
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("out/app/README.md")).unwrap(),
+        "app by Jane Doe, v2.0.0\n"
+    );
+
+    let output = run(vibe(temp.path()).args([
+        "--spec",
+        "project.toml",
+        "--force",
+        "--set",
+        "author=Bob",
+        "--project-version",
+        "3.0.0",
+    ]));
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("out/app/README.md")).unwrap(),
+        "app by Bob, v3.0.0\n"
+    );
+}
+
+#[test]
+fn json_errors_have_stable_kinds_and_english_messages() {
+    let temp = tempfile::tempdir().unwrap();
+    let error = |args: &[&str]| {
+        let output = run(vibe(temp.path())
+            .args(["--lang", "es", "--format", "json"])
+            .args(args));
+        assert!(!output.status.success());
+        stdout_json(&output)["error"].clone()
+    };
+
+    let unknown = error(&["--template", "rust-1.8", "--name", "app"]);
+    assert_eq!(unknown["kind"], "template_not_found");
+    assert!(unknown["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Unknown template"));
+    assert!(unknown["message"].as_str().unwrap().contains("rust-1.9"));
+
+    fs::create_dir(temp.path().join("app")).unwrap();
+    let exists = error(&["--template", "rust-1.9", "--name", "app"]);
+    assert_eq!(exists["kind"], "destination_exists");
+    assert!(exists["path"].as_str().unwrap().ends_with("app"));
+}
+
+#[test]
+fn version_is_checked_however_it_is_set() {
+    let temp = tempfile::tempdir().unwrap();
+    write(
+        temp.path(),
+        "project.toml",
+        "template = \"rust-1.9\"\nname = \"app\"\n\n[vars]\nversion = \"two\"\n",
+    );
+
+    for args in [
+        &["-t", "rust-1.9", "-n", "app", "--set", "version=banana"][..],
+        &["update", "-n", "app", "--set", "version=1.x"],
+        &["--spec", "project.toml"],
+    ] {
+        let output = run(vibe(temp.path()).args(args));
+        assert!(!output.status.success(), "{args:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("is not a valid semantic version"),
+            "{stderr}"
+        );
+    }
+    assert!(!temp.path().join("app").exists());
+
+    // The user config never stops a run; an invalid version voids it.
+    write(
+        temp.path(),
+        ".config/vibe-generate/config.toml",
+        "[vars]\nversion = \"latest\"\n",
+    );
+    write(temp.path(), "templates/card/VERSION", "{{version}}\n");
+    let output = run(vibe(temp.path()).args(["-t", "card", "-n", "app"]));
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Ignoring user config"), "{stderr}");
+    assert!(
+        stderr.contains("is not a valid semantic version"),
+        "{stderr}"
+    );
+    assert_eq!(
+        fs::read_to_string(temp.path().join("app/VERSION")).unwrap(),
+        "0.1.0\n"
+    );
+}
+
+#[test]
+fn update_treats_the_gitignore_merge_as_generated() {
+    let temp = tempfile::tempdir().unwrap();
+    write(temp.path(), "templates/card/.gitignore", "*.log\n");
+    write(
+        temp.path(),
+        "templates/card/README.md",
+        "# {{project-name}}\n",
+    );
+
+    let output = run(vibe(temp.path()).args(["-t", "card", "-n", "app", "--gitignore", "rust"]));
+    assert!(output.status.success(), "{output:?}");
+    let manifest = fs::read_to_string(temp.path().join("app/.vibe-manifest.toml")).unwrap();
+    assert!(manifest.contains("gitignore = \"rust\""), "{manifest}");
+
+    let output = run(vibe(temp.path()).args(["update", "-n", "app"]));
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(" 0 conflicts"), "{stdout}");
+    let gitignore = fs::read_to_string(temp.path().join("app/.gitignore")).unwrap();
+    assert!(gitignore.starts_with("*.log\n"), "{gitignore}");
+    assert!(gitignore.contains("/target\n"), "{gitignore}");
+}
+
+#[test]
+fn config_vars_win_over_built_ins_only() {
+    let temp = tempfile::tempdir().unwrap();
+    write(
+        temp.path(),
+        ".config/vibe-generate/config.toml",
+        "[vars]\nauthor = \"Config Author\"\nlicense = \"Apache-2.0\"\n",
+    );
+    write(
+        temp.path(),
+        "templates/card/template.toml",
+        "[[placeholders]]\nkey = \"license\"\ndefault = \"MIT\"\n",
+    );
+    write(
+        temp.path(),
+        "templates/card/README.md",
+        "by {{author}}, {{license}}\n",
+    );
+    write(
+        temp.path(),
+        "project.toml",
+        "template = \"card\"\nname = \"spec\"\n\n[vars]\nauthor = \"Spec Author\"\n",
+    );
+    let readme = |name: &str| fs::read_to_string(temp.path().join(name).join("README.md")).unwrap();
+
+    let output = run(vibe(temp.path()).args(["-t", "card", "-n", "plain"]));
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(readme("plain"), "by Config Author, MIT\n");
+
+    let output = run(vibe(temp.path()).args(["--spec", "project.toml"]));
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(readme("spec"), "by Spec Author, MIT\n");
+
+    let output = run(vibe(temp.path()).args(["-t", "card", "-n", "set", "--set", "author=Bob"]));
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(readme("set"), "by Bob, MIT\n");
+
+    let output = run(vibe(temp.path()).args(["--print-config", "--format", "json"]));
+    assert!(output.status.success(), "{output:?}");
+    let author = &stdout_json(&output)["vars"]["author"];
+    assert_eq!(author["value"], "Config Author");
+    assert!(author["origin"]
+        .as_str()
+        .unwrap()
+        .starts_with("user config"));
+}
+
+#[test]
+fn explain_source_treats_nested_ids_as_names() {
+    let temp = tempfile::tempdir().unwrap();
+    write(temp.path(), "templates/rust/cli/template.toml", "");
+    write(
+        temp.path(),
+        "templates/rust/cli/src/main.rs",
+        "fn main() {}\n",
+    );
+
+    let explain = |template: &str| {
+        let output =
+            run(vibe(temp.path()).args(["--explain-source", "-t", template, "--format", "json"]));
+        (output.status.success(), stdout_json(&output))
+    };
+    let (ok, explanation) = explain("rust/cli");
+    assert!(ok, "{explanation}");
+    assert_eq!(explanation["kind"], "name");
+    assert!(explanation["resolves_to"]
+        .as_str()
+        .unwrap()
+        .ends_with("cli"));
+
+    let (ok, explanation) = explain("rust/lib");
+    assert!(!ok);
+    assert_eq!(explanation["kind"], "path");
+
+    // A fetched root with its own template.toml is the template, even with
+    // templates nested below it.
+    let repo = temp.path().join("repo");
+    write(&repo, "template.toml", "description = \"Root\"\n");
+    write(&repo, "README.md", "# {{project-name}}\n");
+    write(&repo, "examples/cli/template.toml", "");
+    write(&repo, "examples/cli/main.rs", "// {{project-name}}\n");
+    git_repo(&repo);
+    let repo = repo.to_str().unwrap();
+    let output = run(vibe(temp.path()).args(["--from-git", repo, "-n", "root"]));
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("root/README.md")).unwrap(),
+        "# root\n"
+    );
+    assert!(temp.path().join("root/examples/cli/main.rs").is_file());
+    let output =
+        run(vibe(temp.path()).args(["--from-git", repo, "-t", "examples/cli", "-n", "nested"]));
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("nested/main.rs")).unwrap(),
+        "// nested\n"
+    );
+}
+
+#[test]
+fn bundled_templates_pass_strict() {
+    let temp = tempfile::tempdir().unwrap();
+    for template in ["java-25", "nextjs", "rust-1.9"] {
+        let output =
+            run(vibe(temp.path()).args(["-t", template, "-n", template, "--strict", "--no-hooks"]));
+        assert!(output.status.success(), "{template}: {output:?}");
+    }
+}
+
+#[test]
+fn fetch_errors_have_stable_kinds() {
+    let temp = tempfile::tempdir().unwrap();
+    let repo = temp.path().join("repo");
+    write(&repo, "web/index.html", "<p>{{project-name}}</p>\n");
+    git_repo(&repo);
+    let repo = repo.to_str().unwrap();
+    let error = |args: &[&str]| {
+        let output = run(vibe(temp.path())
+            .args(["--format", "json", "-n", "app"])
+            .args(args));
+        assert!(!output.status.success());
+        stdout_json(&output)["error"].clone()
+    };
+
+    let missing = temp.path().join("missing");
+    let failed = error(&["--from-git", missing.to_str().unwrap()]);
+    assert_eq!(failed["kind"], "clone_failed");
+    assert!(failed["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Could not clone"));
+
+    assert_eq!(
+        error(&["--from-git", repo, "-t", "../web"])["kind"],
+        "invalid_template_path"
+    );
+    assert_eq!(
+        error(&["--from-git", repo, "-t", "api"])["kind"],
+        "template_not_found"
+    );
+
+    write(temp.path(), "web.rar", "");
+    write(temp.path(), "web.tar.gz", "not gzip");
+    assert_eq!(
+        error(&["--from-archive", "web.rar"])["kind"],
+        "unsupported_archive"
+    );
+    assert_eq!(
+        error(&["--from-archive", "web.tar.gz"])["kind"],
+        "extract_failed"
+    );
+}